@@ -2,7 +2,11 @@
 // DO NOT commit config.rs to Git - it should be (already) in .gitignore
 use defmt::Format;
 
+use crate::tasks::display::draw::{ColorScheme, Theme};
+
 // WiFi credentials
+// N.B. These, and the API key, are only fallback defaults, used until secrets are provisioned
+// at runtime over the USB console. They may be left as is, to keep them out of the firmware.
 pub const WIFI_SSID: &str = "your-ssid";
 pub const WIFI_PASSWORD: &str = r"your-wifi-password";
 
@@ -24,17 +28,21 @@ impl WifiConfig {
 
 // Proxy info
 pub const HTTP_PROXY: &str = "https://api.tfl.gov.uk";
+// Set to true only if HTTP_PROXY serves binary (postcard) updates, rather than the raw TFL API
+pub const PROXY_BINARY_UPDATES: bool = false;
 
 // Proxy configuration
 #[derive(Clone, Copy, Format)]
 pub struct ProxyConfig {
     pub http_proxy: &'static str,
+    pub binary_updates: bool,
 }
 
 impl ProxyConfig {
     pub fn new() -> Self {
         Self {
             http_proxy: HTTP_PROXY,
+            binary_updates: PROXY_BINARY_UPDATES,
         }
     }
 }
@@ -65,6 +73,101 @@ impl TflApiRequestConfig {
     }
 }
 
+// Countdown smoothing config
+// How far (seconds) a countdown may increase between requests, beyond the time elapsed
+pub const SMOOTHING_MAX_INCREASE_SECS: u32 = 60;
+// How closely (percent) the latest prediction is followed - 100 disables smoothing
+pub const SMOOTHING_RESPONSIVENESS_PERCENT: u32 = 50;
+
+#[derive(Clone, Copy, Format)]
+pub struct SmoothingConfig {
+    pub max_increase_secs: u32,
+    pub responsiveness_percent: u32,
+}
+
+impl SmoothingConfig {
+    pub const fn new() -> Self {
+        Self {
+            max_increase_secs: SMOOTHING_MAX_INCREASE_SECS,
+            responsiveness_percent: SMOOTHING_RESPONSIVENESS_PERCENT,
+        }
+    }
+}
+
+// Station name config
+// Replacements for station, destination and location names, e.g. ("Upminster", "Upminster (End)"),
+// matched against the name from the TFL API, or the name with suffixes such as "Underground Station" removed
+pub const NAME_OVERRIDES: &[(&str, &str)] = &[];
+
+#[derive(Clone, Copy, Format)]
+pub struct NamesConfig {
+    pub overrides: &'static [(&'static str, &'static str)],
+}
+
+impl NamesConfig {
+    pub const fn new() -> Self {
+        Self {
+            overrides: NAME_OVERRIDES,
+        }
+    }
+}
+
+// UI strings config
+// Replacements for the strings drawn, by key, e.g. ("due", "Due") in place of "<1 min", see the
+// fields of `Strings` in src/tasks/display/strings.rs for the keys, and the values of templates
+pub const STRING_OVERRIDES: &[(&str, &str)] = &[];
+
+#[derive(Clone, Copy, Format)]
+pub struct StringsConfig {
+    pub overrides: &'static [(&'static str, &'static str)],
+}
+
+impl StringsConfig {
+    pub const fn new() -> Self {
+        Self {
+            overrides: STRING_OVERRIDES,
+        }
+    }
+}
+
+// Display refresh config
+// Number of partial (quick) refreshes between full refreshes, which clear any ghosting
+pub const DISPLAY_FULL_REFRESH_INTERVAL: u32 = 10;
+// Set to true to render full refreshes in four level grayscale, partial refreshes remain monochrome
+pub const DISPLAY_GRAYSCALE: bool = false;
+// Set to false to keep the panel controller powered between refreshes, rather than in deep sleep
+pub const DISPLAY_SLEEP_BETWEEN_REFRESHES: bool = true;
+// Layout of the arrivals, Theme::Classic, Theme::DotMatrix to look like the platform departure boards,
+// or Theme::LargeType for only the next train, in the largest type that fits
+pub const DISPLAY_THEME: Theme = Theme::Classic;
+// Set to true to toggle between the theme and Theme::LargeType with a button between GPIO 15 and ground
+pub const DISPLAY_LARGE_TYPE_BUTTON: bool = false;
+// Colors of every screen, ColorScheme::Light for black on white, or ColorScheme::Dark for white on black
+pub const DISPLAY_COLOR_SCHEME: ColorScheme = ColorScheme::Light;
+
+#[derive(Clone, Copy, Format)]
+pub struct DisplayConfig {
+    pub full_refresh_interval: u32,
+    pub grayscale: bool,
+    pub sleep_between_refreshes: bool,
+    pub theme: Theme,
+    pub color_scheme: ColorScheme,
+    pub large_type_button: bool,
+}
+
+impl DisplayConfig {
+    pub const fn new() -> Self {
+        Self {
+            full_refresh_interval: DISPLAY_FULL_REFRESH_INTERVAL,
+            grayscale: DISPLAY_GRAYSCALE,
+            sleep_between_refreshes: DISPLAY_SLEEP_BETWEEN_REFRESHES,
+            theme: DISPLAY_THEME,
+            color_scheme: DISPLAY_COLOR_SCHEME,
+            large_type_button: DISPLAY_LARGE_TYPE_BUTTON,
+        }
+    }
+}
+
 // Active/inactive schedule config
 pub const ACTIVE_AT: (u32, u32, u32) = (6, 30, 0);
 pub const INACTIVE_AT: (u32, u32, u32) = (22, 30, 0);
@@ -83,3 +186,22 @@ impl ScheduleConfig {
         }
     }
 }
+
+// Troubleshooting config
+// Page of the troubleshooting notes, linked by a QR code on the error screens, with the state
+// and any error code appended, e.g. "<page>?code=401#auth-failed"
+pub const TROUBLESHOOTING_PAGE: &str =
+    "https://github.com/its3mile/london-pi-tube/blob/main/README.md";
+
+#[derive(Clone, Copy, Format)]
+pub struct TroubleshootingConfig {
+    pub page: &'static str,
+}
+
+impl TroubleshootingConfig {
+    pub const fn new() -> Self {
+        Self {
+            page: TROUBLESHOOTING_PAGE,
+        }
+    }
+}
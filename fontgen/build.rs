@@ -0,0 +1,23 @@
+//! Encode the sample glyphs
+//!
+//! `u8g2-fonts` only draws fonts built in, so the sample glyphs are encoded
+//! here, for the tests to draw back, see `src/tests.rs`.
+//!
+use std::path::PathBuf;
+
+// Only the encoder is used here
+#[allow(dead_code)]
+#[path = "src/u8g2.rs"]
+mod u8g2;
+
+#[path = "src/sample.rs"]
+mod sample;
+
+fn main() {
+    println!("cargo::rerun-if-changed=src/u8g2.rs");
+    println!("cargo::rerun-if-changed=src/sample.rs");
+
+    let data = u8g2::encode(&sample::glyphs()).expect("Failed to encode the sample glyphs");
+    let out = PathBuf::from(std::env::var_os("OUT_DIR").unwrap());
+    std::fs::write(out.join("sample.u8g2"), data).expect("Failed to write the sample font");
+}
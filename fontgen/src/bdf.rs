@@ -0,0 +1,75 @@
+//! BDF bitmap fonts
+//!
+//! Reads the glyphs of a BDF font, as drawn at its own size, e.g. one of the
+//! X11 fonts, or a font exported from a bitmap font editor.
+//!
+use std::error::Error;
+
+use crate::u8g2::Glyph;
+
+/// Read the glyphs of the characters, skipping any the font is missing
+pub fn glyphs(source: &str, chars: &[char]) -> Result<Vec<Glyph>, Box<dyn Error>> {
+    let mut glyphs = Vec::new();
+    let mut lines = source.lines();
+    while let Some(line) = lines.next() {
+        if !line.starts_with("STARTCHAR") {
+            continue;
+        }
+
+        let mut encoding = None;
+        let mut advance = 0;
+        let mut bounds = None;
+        for line in lines.by_ref() {
+            let mut words = line.split_whitespace();
+            match words.next() {
+                Some("ENCODING") => {
+                    encoding = words
+                        .next()
+                        .and_then(|word| word.parse::<u32>().ok())
+                        .and_then(char::from_u32);
+                }
+                Some("DWIDTH") => advance = parse(words.next())?,
+                Some("BBX") => {
+                    bounds = Some((
+                        parse::<u32>(words.next())?,
+                        parse::<u32>(words.next())?,
+                        parse::<i32>(words.next())?,
+                        parse::<i32>(words.next())?,
+                    ));
+                }
+                Some("BITMAP") => break,
+                _ => {}
+            }
+        }
+        let (width, height, x, y) = bounds.ok_or("Glyph without a BBX")?;
+
+        // Each row in hex, padded to whole bytes, with the leftmost pixel in the top bit
+        let mut bitmap = Vec::with_capacity((width * height) as usize);
+        for _ in 0..height {
+            let row = lines.next().ok_or("Glyph bitmap ended early")?;
+            let row = u128::from_str_radix(row.trim(), 16)?;
+            let row_bits = width.div_ceil(8) * 8;
+            bitmap.extend((0..width).map(|x| row >> (row_bits - 1 - x) & 1 != 0));
+        }
+
+        // Glyphs without a Unicode encoding have an encoding of -1
+        if let Some(encoding) = encoding.filter(|encoding| chars.contains(encoding)) {
+            glyphs.push(Glyph {
+                encoding,
+                width,
+                height,
+                x,
+                y,
+                advance,
+                bitmap,
+            });
+        }
+    }
+
+    Ok(glyphs)
+}
+
+fn parse<T: std::str::FromStr>(word: Option<&str>) -> Result<T, Box<dyn Error>> {
+    word.and_then(|word| word.parse().ok())
+        .ok_or_else(|| format!("Invalid BDF value {:?}", word).into())
+}
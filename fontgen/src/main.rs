@@ -0,0 +1,217 @@
+//! Font table generator
+//!
+//! Rasterises a TTF or OTF font at chosen sizes, or reads a BDF bitmap font,
+//! into the font format of u8g2, as drawn by the firmware with `u8g2-fonts`.
+//! The board can then use a typeface other than the stock u8g2 fonts, e.g. a
+//! Johnston-like sans, see the `typeface` feature of the firmware.
+//!
+//! Each table is subset to the glyphs the board draws, to save flash: the
+//! printable ASCII of the TFL names and times, and the other characters of
+//! the string tables, e.g. the accents of the French strings.
+//!
+//! Usage:
+//!
+//! cargo run --release -- --regular <font> --bold <font> [--glyphs <file>]...
+//!
+//! Writes each table of the `typeface` feature, to `src/tasks/display/typeface`
+//! of the firmware, see `typeface.rs`.
+//!
+//! cargo run --release -- --font <font> [--size <px>] --out <file> [--glyphs <file>]...
+//!
+//! Writes a single table, e.g. to try out a font or size.
+//!
+//! - `<font>`: a TTF or OTF outline font, rasterised at each size, or for a
+//!   single table, a BDF bitmap font, drawn at its own size
+//! - `--size`: the size of the em in pixels, as u8g2 fonts are named (default 14)
+//! - `--glyphs`: also include the characters of the file, e.g. `../src/config.rs`
+//!   for any string overrides (default only the string tables)
+//!
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use ttf::{OutlineFont, Size};
+
+mod bdf;
+mod ttf;
+mod u8g2;
+
+// Only used by the tests
+#[cfg(test)]
+mod sample;
+#[cfg(test)]
+mod tests;
+
+// Tables of the `typeface` feature, by name, weight and size, see the firmware `typeface.rs`
+const TYPEFACE: &[(&str, Weight, Size)] = &[
+    ("regular_8", Weight::Regular, Size::Em(8)),
+    ("regular_10", Weight::Regular, Size::Em(10)),
+    ("regular_14", Weight::Regular, Size::Em(14)),
+    ("regular_24", Weight::Regular, Size::Em(24)),
+    ("bold_8", Weight::Bold, Size::Em(8)),
+    ("bold_10", Weight::Bold, Size::Em(10)),
+    ("bold_14", Weight::Bold, Size::Em(14)),
+    ("bold_18", Weight::Bold, Size::Em(18)),
+    ("bold_24", Weight::Bold, Size::Em(24)),
+    // In place of the logisoso destination names, so sized by the capitals
+    ("display_16", Weight::Bold, Size::Cap(16)),
+    ("display_24", Weight::Bold, Size::Cap(24)),
+    ("display_38", Weight::Bold, Size::Cap(38)),
+];
+
+// String tables of the firmware, relative to this crate
+const STRINGS: &str = "../src/tasks/display/strings.rs";
+
+// Tables of the `typeface` feature, relative to this crate
+const TYPEFACE_DIR: &str = "../src/tasks/display/typeface";
+
+#[derive(Copy, Clone, Debug)]
+enum Weight {
+    Regular,
+    Bold,
+}
+
+/// What to generate
+enum Mode {
+    Typeface {
+        regular: PathBuf,
+        bold: PathBuf,
+    },
+    Single {
+        font: PathBuf,
+        size: u32,
+        out: PathBuf,
+    },
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> Result<(), Box<dyn Error>> {
+    let (mode, glyph_files) = parse_args()?;
+    let chars = chars(&glyph_files)?;
+
+    match mode {
+        Mode::Typeface { regular, bold } => {
+            let regular = outline_font(&regular)?;
+            let bold = outline_font(&bold)?;
+            let dir = crate_dir().join(TYPEFACE_DIR);
+            std::fs::create_dir_all(&dir)?;
+            for (name, weight, size) in TYPEFACE {
+                let font = match weight {
+                    Weight::Regular => &regular,
+                    Weight::Bold => &bold,
+                };
+                let glyphs = font.glyphs(&chars, *size);
+                write(&glyphs, &chars, &dir.join(format!("{}.u8g2", name)))?;
+            }
+        }
+        Mode::Single { font, size, out } => {
+            let glyphs = match font.extension().and_then(|extension| extension.to_str()) {
+                Some("bdf") => bdf::glyphs(&std::fs::read_to_string(&font)?, &chars)?,
+                _ => outline_font(&font)?.glyphs(&chars, Size::Em(size)),
+            };
+            write(&glyphs, &chars, &out)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Encode the glyphs, cropped to their ink, and write the table
+fn write(glyphs: &[u8g2::Glyph], chars: &[char], path: &Path) -> Result<(), Box<dyn Error>> {
+    let missing: String = chars
+        .iter()
+        .filter(|&&c| !glyphs.iter().any(|glyph| glyph.encoding == c))
+        .collect();
+    if !missing.is_empty() {
+        eprintln!("{}: font is missing {:?}", path.display(), missing);
+    }
+
+    let glyphs: Vec<u8g2::Glyph> = glyphs.iter().cloned().map(u8g2::Glyph::cropped).collect();
+    let data = u8g2::encode(&glyphs)?;
+    std::fs::write(path, &data)?;
+    println!(
+        "Wrote {}, {} glyphs in {} bytes",
+        path.display(),
+        glyphs.len(),
+        data.len()
+    );
+
+    Ok(())
+}
+
+fn outline_font(path: &Path) -> Result<OutlineFont, Box<dyn Error>> {
+    std::fs::read(path)
+        .map_err(Box::from)
+        .and_then(|data| OutlineFont::new(&data))
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e).into())
+}
+
+/// Printable ASCII, and the other Latin-1 characters of the string tables and files
+fn chars(files: &[PathBuf]) -> Result<Vec<char>, Box<dyn Error>> {
+    let mut chars: Vec<char> = (' '..='~').collect();
+    for file in std::iter::once(crate_dir().join(STRINGS)).chain(files.iter().cloned()) {
+        let text = std::fs::read_to_string(&file)
+            .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+        for c in text.chars().filter(|c| !c.is_control()) {
+            if chars.contains(&c) {
+                continue;
+            }
+            if u32::from(c) > 0xff {
+                eprintln!("{}: skipping {:?}, outside Latin-1", file.display(), c);
+                continue;
+            }
+            chars.push(c);
+        }
+    }
+    chars.sort();
+
+    Ok(chars)
+}
+
+fn crate_dir() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+}
+
+fn parse_args() -> Result<(Mode, Vec<PathBuf>), Box<dyn Error>> {
+    let mut regular = None;
+    let mut bold = None;
+    let mut font = None;
+    let mut size = 14;
+    let mut out = None;
+    let mut glyph_files = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("Missing value for {}", arg));
+        match arg.as_str() {
+            "--regular" => regular = Some(PathBuf::from(value()?)),
+            "--bold" => bold = Some(PathBuf::from(value()?)),
+            "--font" => font = Some(PathBuf::from(value()?)),
+            "--size" => size = value()?.parse()?,
+            "--out" => out = Some(PathBuf::from(value()?)),
+            "--glyphs" => glyph_files.push(PathBuf::from(value()?)),
+            _ => return Err(format!("Unknown argument {}", arg).into()),
+        }
+    }
+
+    let mode = match (regular, bold, font, out) {
+        (Some(regular), Some(bold), None, None) => Mode::Typeface { regular, bold },
+        (None, None, Some(font), Some(out)) => Mode::Single { font, size, out },
+        _ => {
+            return Err(
+                "Expected --regular <font> --bold <font>, or --font <font> --out <file>".into(),
+            );
+        }
+    };
+
+    Ok((mode, glyph_files))
+}
@@ -0,0 +1,82 @@
+//! Sample glyphs
+//!
+//! Glyphs of a spread of sizes, offsets and ink, encoded by the build script
+//! for the tests to draw back with `u8g2-fonts`, see `tests.rs`.
+//!
+use crate::u8g2::Glyph;
+
+// Seed of the pseudo-random glyphs, so they are the same for the build script and the tests
+const SEED: u32 = 0x2545_f491;
+
+/// Printable ASCII, and a few Latin-1 glyphs
+pub fn glyphs() -> Vec<Glyph> {
+    let mut random = Random(SEED);
+    (' '..='~')
+        .chain(['£', 'é', 'ÿ'])
+        .map(|encoding| match encoding {
+            // No ink, only an advance
+            ' ' => Glyph {
+                encoding,
+                width: 0,
+                height: 0,
+                x: 0,
+                y: 0,
+                advance: 4,
+                bitmap: Vec::new(),
+            },
+            // Solid, for runs longer than the widest run field
+            '#' => Glyph {
+                encoding,
+                width: 20,
+                height: 24,
+                x: -1,
+                y: -4,
+                advance: 20,
+                bitmap: vec![true; 20 * 24],
+            },
+            _ => random.glyph(encoding),
+        })
+        .collect()
+}
+
+/// Xorshift, as a dependency free source of glyphs
+struct Random(u32);
+
+impl Random {
+    fn next(&mut self) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0
+    }
+
+    fn range(&mut self, min: i32, max: i32) -> i32 {
+        min + (self.next() % (max - min + 1) as u32) as i32
+    }
+
+    /// A glyph of random size and offsets, of ink in random runs
+    fn glyph(&mut self, encoding: char) -> Glyph {
+        let width = self.range(1, 16) as u32;
+        let height = self.range(1, 24) as u32;
+        let x = self.range(-3, 3);
+        let mut ink = false;
+        let bitmap = (0..width * height)
+            .map(|_| {
+                if self.next().is_multiple_of(4) {
+                    ink = !ink;
+                }
+                ink
+            })
+            .collect();
+
+        Glyph {
+            encoding,
+            width,
+            height,
+            x,
+            y: self.range(-6, 12),
+            advance: x + width as i32 + self.range(-1, 2),
+            bitmap,
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Font table tests
+//!
+//! Draws each of the sample glyphs, as encoded by the build script, with
+//! `u8g2-fonts`, as the firmware draws, and checks that the metrics and the
+//! bitmap of each glyph read back are those encoded.
+//!
+use std::collections::BTreeSet;
+use std::convert::Infallible;
+
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use u8g2_fonts::types::{FontColor, VerticalPosition};
+use u8g2_fonts::{Error, FontRenderer, LookupError};
+
+use crate::sample;
+use crate::u8g2::Glyph;
+
+// Origin of each glyph, on the baseline, clear of the edges of the canvas
+const ORIGIN: Point = Point::new(64, 64);
+
+struct SampleFont;
+
+impl u8g2_fonts::Font for SampleFont {
+    const DATA: &'static [u8] = include_bytes!(concat!(env!("OUT_DIR"), "/sample.u8g2"));
+}
+
+/// Points drawn, as (x, y)
+#[derive(Default)]
+struct Canvas(BTreeSet<(i32, i32)>);
+
+impl OriginDimensions for Canvas {
+    fn size(&self) -> Size {
+        Size::new_equal(128)
+    }
+}
+
+impl DrawTarget for Canvas {
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, _) in pixels {
+            self.0.insert((point.x, point.y));
+        }
+        Ok(())
+    }
+}
+
+/// Points of the glyph ink, drawn at the origin
+fn ink(glyph: &Glyph) -> BTreeSet<(i32, i32)> {
+    let top = ORIGIN.y - glyph.y - glyph.height as i32;
+    (0..glyph.height)
+        .flat_map(|y| (0..glyph.width).map(move |x| (x, y)))
+        .filter(|(x, y)| glyph.bitmap[(x + y * glyph.width) as usize])
+        .map(|(x, y)| (ORIGIN.x + glyph.x + x as i32, top + y as i32))
+        .collect()
+}
+
+#[test]
+fn glyphs_draw_as_encoded() {
+    let font = FontRenderer::new::<SampleFont>();
+
+    for glyph in sample::glyphs() {
+        let mut canvas = Canvas::default();
+        let dimensions = font
+            .render(
+                glyph.encoding,
+                ORIGIN,
+                VerticalPosition::Baseline,
+                FontColor::Transparent(BinaryColor::On),
+                &mut canvas,
+            )
+            .unwrap();
+
+        assert_eq!(dimensions.advance.x, glyph.advance, "{:?}", glyph.encoding);
+        let bounds = (glyph.width > 0).then(|| {
+            Rectangle::new(
+                ORIGIN + Point::new(glyph.x, -glyph.y - glyph.height as i32),
+                Size::new(glyph.width, glyph.height),
+            )
+        });
+        assert_eq!(dimensions.bounding_box, bounds, "{:?}", glyph.encoding);
+        assert_eq!(canvas.0, ink(&glyph), "{:?}", glyph.encoding);
+    }
+}
+
+#[test]
+fn font_metrics_are_of_the_ascii_glyphs() {
+    let font = FontRenderer::new::<SampleFont>();
+    let ascii: Vec<Glyph> = sample::glyphs()
+        .into_iter()
+        .filter(|glyph| glyph.encoding.is_ascii() && glyph.width > 0)
+        .collect();
+
+    let ascent = ascii
+        .iter()
+        .map(|glyph| glyph.y + glyph.height as i32)
+        .max();
+    let descent = ascii.iter().map(|glyph| glyph.y).min();
+    assert_eq!(Some(i32::from(font.get_ascent())), ascent);
+    assert_eq!(Some(i32::from(font.get_descent())), descent);
+}
+
+#[test]
+fn missing_glyphs_are_not_found() {
+    let font = FontRenderer::new::<SampleFont>();
+
+    for encoding in ['\u{1}', '\u{a0}', '€'] {
+        let result = font.render(
+            encoding,
+            ORIGIN,
+            VerticalPosition::Baseline,
+            FontColor::Transparent(BinaryColor::On),
+            &mut Canvas::default(),
+        );
+        assert!(
+            matches!(result, Err(Error::GlyphNotFound(c)) if c == encoding),
+            "{:?}",
+            encoding
+        );
+        assert!(matches!(
+            font.get_rendered_dimensions(encoding, ORIGIN, VerticalPosition::Baseline),
+            Err(LookupError::GlyphNotFound(_))
+        ));
+    }
+}
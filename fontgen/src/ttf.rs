@@ -0,0 +1,65 @@
+//! TTF and OTF outline fonts
+//!
+//! Rasterises the glyphs of an outline font at a size in pixels, with `fontdue`.
+//! The panel only shows black or white, so each pixel is inked where the
+//! glyph covers at least half of it.
+//!
+use std::error::Error;
+
+use fontdue::{Font, FontSettings};
+
+use crate::u8g2::Glyph;
+
+// Coverage of a pixel from which it is inked
+const INK_COVERAGE: u8 = 128;
+
+// Size to measure the cap height at, to size fonts by it
+const MEASURE_SIZE: f32 = 100.0;
+
+/// Size of a font, in pixels
+#[derive(Copy, Clone, Debug)]
+pub enum Size {
+    /// Of the em, as u8g2 and BDF fonts are named, e.g. "helvR14"
+    Em(u32),
+    /// Of the capitals, as the numerals of e.g. "logisoso24" are
+    Cap(u32),
+}
+
+pub struct OutlineFont {
+    font: Font,
+}
+
+impl OutlineFont {
+    pub fn new(data: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let font = Font::from_bytes(data, FontSettings::default())?;
+        Ok(Self { font })
+    }
+
+    /// Rasterise the glyphs of the characters at the size, skipping any the font is missing
+    pub fn glyphs(&self, chars: &[char], size: Size) -> Vec<Glyph> {
+        let px = match size {
+            Size::Em(px) => px as f32,
+            Size::Cap(px) => {
+                let (metrics, _) = self.font.rasterize('H', MEASURE_SIZE);
+                px as f32 * MEASURE_SIZE / metrics.height.max(1) as f32
+            }
+        };
+
+        chars
+            .iter()
+            .filter(|&&c| self.font.lookup_glyph_index(c) != 0)
+            .map(|&c| {
+                let (metrics, coverage) = self.font.rasterize(c, px);
+                Glyph {
+                    encoding: c,
+                    width: metrics.width as u32,
+                    height: metrics.height as u32,
+                    x: metrics.xmin,
+                    y: metrics.ymin,
+                    advance: metrics.advance_width.round() as i32,
+                    bitmap: coverage.iter().map(|&c| c >= INK_COVERAGE).collect(),
+                }
+            })
+            .collect()
+    }
+}
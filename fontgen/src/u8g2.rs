@@ -0,0 +1,297 @@
+//! u8g2 font format
+//!
+//! Encodes glyph bitmaps into the font format of u8g2, as drawn by the
+//! `u8g2-fonts` crate: a 23 byte header of the font metrics and the bit
+//! widths of the glyph fields, then each glyph as its bounding box and a run
+//! length encoded bitmap, packed least significant bit first.
+//!
+//! Only glyphs up to U+00FF are encoded, which covers the Latin-1 accents, so
+//! the Unicode glyph table is left empty.
+//!
+use std::error::Error;
+
+// Size of the header, before the glyphs
+const HEADER_SIZE: usize = 23;
+
+// Range of the bit widths of the zero and one runs of the bitmaps, the best of which is chosen
+const RUN_BITS: std::ops::RangeInclusive<u32> = 2..=8;
+
+/// A glyph bitmap, and its position relative to the origin on the baseline
+#[derive(Clone, Debug)]
+pub struct Glyph {
+    pub encoding: char,
+    pub width: u32,
+    pub height: u32,
+    // Offset of the left of the bitmap, to the right of the origin
+    pub x: i32,
+    // Offset of the bottom of the bitmap, above the baseline
+    pub y: i32,
+    // Advance to the origin of the next glyph
+    pub advance: i32,
+    // Row major, from the top, set for ink
+    pub bitmap: Vec<bool>,
+}
+
+impl Glyph {
+    /// The glyph cropped to the bounding box of its ink, or empty if it has none (e.g. a space)
+    pub fn cropped(self) -> Self {
+        let ink = |x: u32, y: u32| self.bitmap[(x + y * self.width) as usize];
+        let rows: Vec<u32> = (0..self.height)
+            .filter(|&y| (0..self.width).any(|x| ink(x, y)))
+            .collect();
+        let columns: Vec<u32> = (0..self.width)
+            .filter(|&x| (0..self.height).any(|y| ink(x, y)))
+            .collect();
+
+        let (Some(&top), Some(&bottom), Some(&left), Some(&right)) =
+            (rows.first(), rows.last(), columns.first(), columns.last())
+        else {
+            return Self {
+                width: 0,
+                height: 0,
+                x: 0,
+                y: 0,
+                bitmap: Vec::new(),
+                ..self
+            };
+        };
+
+        let bitmap = (top..=bottom)
+            .flat_map(|y| (left..=right).map(move |x| (x, y)))
+            .map(|(x, y)| ink(x, y))
+            .collect();
+        Self {
+            width: right - left + 1,
+            height: bottom - top + 1,
+            x: self.x + left as i32,
+            y: self.y + (self.height - 1 - bottom) as i32,
+            bitmap,
+            ..self
+        }
+    }
+
+    // Top of the bitmap, above the baseline
+    fn top(&self) -> i32 {
+        self.y + self.height as i32
+    }
+}
+
+/// Bit widths of the glyph fields, shared by every glyph of the font
+#[derive(Copy, Clone, Debug)]
+struct Fields {
+    zeros: u32,
+    ones: u32,
+    width: u32,
+    height: u32,
+    x: u32,
+    y: u32,
+    advance: u32,
+}
+
+/// Encode the glyphs as a u8g2 font
+pub fn encode(glyphs: &[Glyph]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut glyphs: Vec<&Glyph> = glyphs.iter().collect();
+    glyphs.sort_by_key(|glyph| glyph.encoding);
+    if let Some(glyph) = glyphs.iter().find(|glyph| glyph.encoding as u32 > 0xff) {
+        return Err(format!("Glyph {:?} is outside Latin-1", glyph.encoding).into());
+    }
+    if glyphs.is_empty() || glyphs.len() > 0xff {
+        return Err(format!("Expected 1 to 255 glyphs, found {}", glyphs.len()).into());
+    }
+
+    // Field widths large enough for every glyph, with the run widths giving the smallest font
+    let max = |field: fn(&Glyph) -> i32| glyphs.iter().map(|glyph| field(glyph)).max().unwrap();
+    let min = |field: fn(&Glyph) -> i32| glyphs.iter().map(|glyph| field(glyph)).min().unwrap();
+    let signed = |field: fn(&Glyph) -> i32| signed_bits(min(field)).max(signed_bits(max(field)));
+    let fields = RUN_BITS
+        .flat_map(|zeros| RUN_BITS.map(move |ones| (zeros, ones)))
+        .map(|(zeros, ones)| Fields {
+            zeros,
+            ones,
+            width: unsigned_bits(max(|glyph| glyph.width as i32) as u32),
+            height: unsigned_bits(max(|glyph| glyph.height as i32) as u32),
+            x: signed(|glyph| glyph.x),
+            y: signed(|glyph| glyph.y),
+            advance: signed(|glyph| glyph.advance),
+        })
+        .min_by_key(|&fields| {
+            glyphs
+                .iter()
+                .map(|glyph| encode_glyph(glyph, fields).len())
+                .sum::<usize>()
+        })
+        .unwrap();
+    if [
+        fields.width,
+        fields.height,
+        fields.x,
+        fields.y,
+        fields.advance,
+    ]
+    .iter()
+    .any(|&bits| bits > 8)
+    {
+        return Err("Glyphs are too large for the u8g2 format, use a smaller size".into());
+    }
+
+    // Bounding box of every glyph with ink
+    let inked: Vec<&&Glyph> = glyphs.iter().filter(|glyph| glyph.width > 0).collect();
+    let left = inked.iter().map(|glyph| glyph.x).min().unwrap_or(0);
+    let right = inked
+        .iter()
+        .map(|glyph| glyph.x + glyph.width as i32)
+        .max()
+        .unwrap_or(0);
+    let bottom = inked.iter().map(|glyph| glyph.y).min().unwrap_or(0);
+    let top = inked.iter().map(|glyph| glyph.top()).max().unwrap_or(0);
+    let find = |encoding: char| glyphs.iter().find(|glyph| glyph.encoding == encoding);
+
+    // Ascent and descent of the ASCII glyphs, rather than of 'A' and 'g' as u8g2, as the firmware
+    // aligns text by its top, and the ascenders of many typefaces are taller than the capitals
+    let ascii = inked.iter().filter(|glyph| glyph.encoding.is_ascii());
+    let ascent = ascii.clone().map(|glyph| glyph.top()).max().unwrap_or(top);
+    let descent = ascii.map(|glyph| glyph.y).min().unwrap_or(bottom);
+
+    let mut data = vec![
+        glyphs.len() as u8,
+        // Proportional, each glyph with its own bounding box
+        0,
+        fields.zeros as u8,
+        fields.ones as u8,
+        fields.width as u8,
+        fields.height as u8,
+        fields.x as u8,
+        fields.y as u8,
+        fields.advance as u8,
+        metric(right - left)?,
+        metric(top - bottom)?,
+        metric(left)?,
+        metric(bottom)?,
+        metric(ascent)?,
+        metric(descent)?,
+        metric(find('(').map_or(top, |glyph| glyph.top()))?,
+        metric(find('(').map_or(bottom, |glyph| glyph.y))?,
+    ];
+    // Offsets of the first glyphs from 'A' and 'a', then the Unicode table, filled in below
+    data.resize(HEADER_SIZE, 0);
+
+    let mut upper_a = None;
+    let mut lower_a = None;
+    for glyph in glyphs.iter() {
+        let offset = data.len() - HEADER_SIZE;
+        if glyph.encoding >= 'A' {
+            upper_a.get_or_insert(offset);
+        }
+        if glyph.encoding >= 'a' {
+            lower_a.get_or_insert(offset);
+        }
+
+        let bits = encode_glyph(glyph, fields);
+        let jump = u8::try_from(bits.len() + 2)
+            .map_err(|_| format!("Glyph {:?} is too large to encode", glyph.encoding))?;
+        data.extend([glyph.encoding as u8, jump]);
+        data.extend(bits);
+    }
+    // End of the glyphs, with a jump of 0, where the search for a missing 'A' or 'a' starts
+    let end = data.len() - HEADER_SIZE;
+    data.extend([0, 0]);
+
+    let unicode = data.len() - HEADER_SIZE;
+    for (pos, offset) in [(17, upper_a), (19, lower_a), (21, Some(unicode))] {
+        let offset = u16::try_from(offset.unwrap_or(end))
+            .map_err(|_| "Too many glyphs to encode, use a smaller size or fewer glyphs")?;
+        data[pos..pos + 2].copy_from_slice(&offset.to_be_bytes());
+    }
+    // Unicode lookup table of one entry, past the last encoding, then the end of no glyphs
+    data.extend([0, 4, 0xff, 0xff, 0, 0]);
+
+    Ok(data)
+}
+
+/// The bounding box and run length encoded bitmap of the glyph
+fn encode_glyph(glyph: &Glyph, fields: Fields) -> Vec<u8> {
+    let mut bits = BitWriter::default();
+    bits.unsigned(glyph.width, fields.width);
+    bits.unsigned(glyph.height, fields.height);
+    bits.signed(glyph.x, fields.x);
+    bits.signed(glyph.y, fields.y);
+    bits.signed(glyph.advance, fields.advance);
+    if glyph.width == 0 {
+        return bits.finish();
+    }
+
+    // Runs of up to the maximum zeros, then up to the maximum ones, through the rows
+    let (max_zeros, max_ones) = ((1 << fields.zeros) - 1, (1 << fields.ones) - 1);
+    let mut runs = Vec::new();
+    let mut pixels = glyph.bitmap.iter().peekable();
+    while pixels.peek().is_some() {
+        let mut zeros = 0;
+        while zeros < max_zeros && pixels.next_if(|&&ink| !ink).is_some() {
+            zeros += 1;
+        }
+        let mut ones = 0;
+        while ones < max_ones && pixels.next_if(|&&ink| ink).is_some() {
+            ones += 1;
+        }
+        runs.push((zeros, ones));
+    }
+
+    // Each run, followed by a set bit for each time it repeats, then a clear bit
+    let mut runs = runs.into_iter().peekable();
+    while let Some(run) = runs.next() {
+        bits.unsigned(run.0, fields.zeros);
+        bits.unsigned(run.1, fields.ones);
+        while runs.next_if_eq(&run).is_some() {
+            bits.unsigned(1, 1);
+        }
+        bits.unsigned(0, 1);
+    }
+
+    bits.finish()
+}
+
+/// Bits packed least significant first, as read by u8g2
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    len: u32,
+}
+
+impl BitWriter {
+    fn unsigned(&mut self, value: u32, bits: u32) {
+        for bit in 0..bits {
+            if self.len.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            if value >> bit & 1 != 0 {
+                *self.bytes.last_mut().unwrap() |= 1 << (self.len % 8);
+            }
+            self.len += 1;
+        }
+    }
+
+    /// Offset by half the range, as u8g2 reads signed fields
+    fn signed(&mut self, value: i32, bits: u32) {
+        self.unsigned((value + (1 << (bits - 1))) as u32, bits);
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// Bits to hold the value, at least one
+fn unsigned_bits(value: u32) -> u32 {
+    (u32::BITS - value.leading_zeros()).max(1)
+}
+
+/// Bits to hold the value, offset by half the range, at least two
+fn signed_bits(value: i32) -> u32 {
+    let magnitude = if value < 0 { !value } else { value } as u32;
+    (unsigned_bits(magnitude) + 1).max(2)
+}
+
+/// A metric of the header, which are signed bytes
+fn metric(value: i32) -> Result<u8, Box<dyn Error>> {
+    Ok(i8::try_from(value).map_err(|_| "Font metric out of range, use a smaller size")? as u8)
+}
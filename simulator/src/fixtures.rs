@@ -0,0 +1,235 @@
+//! Updates to draw
+//!
+//! Named fixtures, or an update built from a saved TFL arrivals response, as
+//! the request task would.
+//!
+use std::error::Error;
+
+use heapless::Vec;
+
+use crate::config::NamesConfig;
+use crate::filters::names::NameNormaliser;
+use crate::models::boot::BootProgress;
+use crate::models::bounded::BoundedString;
+use crate::models::prediction::{ARRAY_MAX_SIZE_PREDICTION_MODEL, Prediction};
+use crate::models::system::SystemState;
+use crate::models::update::{Fault, Update};
+
+pub const FIXTURES: &[&str] = &[
+    "arrivals",
+    "empty",
+    "severe-delays",
+    "long-names",
+    "many-arrivals",
+    "fault",
+    "planned-closures",
+    "proxy-message",
+];
+
+/// System states, shown in place of the arrivals
+pub const STATES: &[(&str, SystemState)] = &[
+    ("joining-wifi", SystemState::JoiningWifi { status: None }),
+    ("wifi-failed", SystemState::JoiningWifi { status: Some(1) }),
+    ("no-ip", SystemState::NoIp),
+    ("dns-failed", SystemState::DnsFailed),
+    ("tls-failed", SystemState::TlsFailed),
+    ("auth-failed", SystemState::AuthFailed { status: 401 }),
+    (
+        "request-failed",
+        SystemState::RequestFailed { code: Some(503) },
+    ),
+    ("no-predictions", SystemState::NoPredictions),
+];
+
+/// Steps of the boot progress checklist
+pub const BOOT_PROGRESS: &[(&str, BootProgress)] = &[
+    ("boot-started", BootProgress::new()),
+    (
+        "boot-joined",
+        BootProgress {
+            wifi: true,
+            ..BootProgress::new()
+        },
+    ),
+    (
+        "boot-fetching",
+        BootProgress {
+            wifi: true,
+            address: Some([192, 168, 1, 23]),
+            time: true,
+            tfl: false,
+        },
+    ),
+];
+
+/// Get a fixture by name
+pub fn fixture(name: &str) -> Option<Update> {
+    let update = match name {
+        "arrivals" => update(
+            "Good Service",
+            &[
+                ("Upminster", "Between Putney Bridge and East Putney", 45),
+                ("Barking", "At Putney Bridge Platform 1", 150),
+                ("Upminster", "Left Parsons Green", 420),
+                ("Tower Hill", "At Wimbledon Park", 780),
+            ],
+        ),
+        "empty" => update("Good Service", &[]),
+        "severe-delays" => {
+            let mut update = update(
+                "Severe Delays",
+                &[
+                    ("Upminster", "At Fulham Broadway", 540),
+                    ("Barking", "Between West Brompton and Earl's Court", 1260),
+                ],
+            );
+            // Trains missing from the latest response, as the tracker retains them
+            for arrival in update.arrivals.iter_mut() {
+                arrival.uncertain = true;
+            }
+            update
+        }
+        "long-names" => Update {
+            line_name: BoundedString::truncating("Hammersmith & City"),
+            platform_name: BoundedString::truncating(
+                "Westbound - Platform 2 - Towards Hammersmith",
+            ),
+            station_name: BoundedString::truncating("King's Cross St. Pancras"),
+            ..update(
+                "Minor Delays",
+                &[
+                    (
+                        "Hammersmith via Shepherd's Bush Market and Goldhawk Road",
+                        "Between Great Portland Street and Baker Street Junction Approach",
+                        30,
+                    ),
+                    (
+                        "Heathrow Terminals 2 & 3 via Acton Town",
+                        "At Euston Square",
+                        95,
+                    ),
+                    ("Edgware Road via Paddington", "At Farringdon", 610),
+                ],
+            )
+        },
+        // More arrivals than the update holds, which are dropped
+        "many-arrivals" => update(
+            "Good Service",
+            &[
+                ("Upminster", "At Putney Bridge", 60),
+                ("Barking", "Left Parsons Green", 180),
+                ("Upminster", "At Fulham Broadway", 300),
+                ("Tower Hill", "At West Brompton", 420),
+                ("Upminster", "At Earl's Court", 540),
+                ("Barking", "At Gloucester Road", 660),
+                ("Upminster", "At South Kensington", 780),
+                ("Tower Hill", "At Sloane Square", 900),
+                ("Upminster", "At Victoria", 1020),
+                ("Barking", "At St. James's Park", 1140),
+            ],
+        ),
+        "fault" => Update {
+            fault: Some(Fault::SchemaMismatch {
+                expected: 2,
+                found: 1,
+            }),
+            ..update("Good Service", &[])
+        },
+        // Only shown on the sleep screen
+        "planned-closures" => Update {
+            closures: [
+                "Saturday 25 and Sunday 26 October, no service between Earl's Court and Wimbledon.",
+                "Sunday 26 October, no service between Tower Hill and Barking.",
+            ]
+            .into_iter()
+            .map(BoundedString::truncating)
+            .collect(),
+            ..update("Part Closure", &[("Upminster", "At Putney Bridge", 60)])
+        },
+        "proxy-message" => Update {
+            generated_at: Some(1_761_390_000),
+            messages: [BoundedString::truncating(
+                "TfL API unreachable, serving the update cached at 12:30",
+            )]
+            .into_iter()
+            .collect(),
+            ..update(
+                "Good Service",
+                &[
+                    ("Upminster", "Between Putney Bridge and East Putney", 45),
+                    ("Barking", "At Putney Bridge Platform 1", 150),
+                ],
+            )
+        },
+        _ => return None,
+    };
+    Some(update)
+}
+
+/// Build an update from a response saved from the TFL arrivals API
+pub fn from_arrivals(json: &str, line_status: &str) -> Result<Update, Box<dyn Error>> {
+    let mut predictions: std::vec::Vec<Prediction> = serde_json::from_str(json)?;
+    predictions.sort_by_key(|prediction| prediction.time_to_station);
+
+    let mut arrivals = predictions
+        .into_iter()
+        .take(ARRAY_MAX_SIZE_PREDICTION_MODEL)
+        .collect::<Vec<Prediction, ARRAY_MAX_SIZE_PREDICTION_MODEL>>();
+    NameNormaliser::new(NamesConfig::new()).process(&mut arrivals);
+
+    // The header is taken from the first arrival, as the request task does
+    let first = arrivals.first().ok_or("No arrivals in the response")?;
+    Ok(Update {
+        line_name: first.line_name.clone(),
+        line_status: BoundedString::truncating(line_status),
+        platform_name: first.platform_name.clone(),
+        station_name: first.station_name.clone(),
+        fault: None,
+        closures: Vec::new(),
+        generated_at: None,
+        messages: Vec::new(),
+        arrivals,
+    })
+}
+
+/// An update at East Putney, with arrivals of (destination, current location, seconds to station)
+fn update(line_status: &str, arrivals: &[(&str, &str, u32)]) -> Update {
+    Update {
+        arrivals: arrivals
+            .iter()
+            .take(ARRAY_MAX_SIZE_PREDICTION_MODEL)
+            .enumerate()
+            .map(|(idx, (destination, location, time_to_station))| {
+                prediction(idx, destination, location, *time_to_station)
+            })
+            .collect(),
+        line_name: BoundedString::truncating("District"),
+        line_status: BoundedString::truncating(line_status),
+        platform_name: BoundedString::truncating("Eastbound - Platform 1"),
+        station_name: BoundedString::truncating("East Putney"),
+        fault: None,
+        closures: Vec::new(),
+        generated_at: None,
+        messages: Vec::new(),
+    }
+}
+
+/// A prediction at East Putney, of the vehicle with the id `idx`
+pub fn prediction(
+    idx: usize,
+    destination: &str,
+    location: &str,
+    time_to_station: u32,
+) -> Prediction {
+    Prediction {
+        vehicle_id: BoundedString::truncating(&idx.to_string()),
+        station_name: BoundedString::truncating("East Putney"),
+        destination_name: BoundedString::truncating(destination),
+        line_name: BoundedString::truncating("District"),
+        platform_name: BoundedString::truncating("Eastbound - Platform 1"),
+        timestamp: BoundedString::truncating("2025-01-01T12:00:00Z"),
+        time_to_station,
+        current_location: BoundedString::truncating(location),
+        uncertain: false,
+    }
+}
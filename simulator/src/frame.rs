@@ -0,0 +1,183 @@
+//! Drawn frames
+//!
+//! Reads back the display buffers as shown on the panel, i.e. in the rotated
+//! coordinates, to write to a PNG, compare against a snapshot, or show in a
+//! window.
+//!
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+use embedded_graphics::pixelcolor::Gray2;
+use embedded_graphics::prelude::*;
+use epd_waveshare::color::Color;
+use epd_waveshare::epd3in7::{Display3in7, HEIGHT, WIDTH};
+
+use crate::tasks::display::gray4::{Gray4Display, SmoothEdges};
+
+// Bytes per row of the monochrome buffer, in the native (portrait) orientation of the panel
+const ROW_BYTES: usize = WIDTH as usize / 8;
+
+/// The pixels of a drawn screen
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    pub size: Size,
+    pub pixels: Vec<Gray2>,
+}
+
+impl Frame {
+    /// Read back a monochrome display, rotated 270 degrees as by the firmware
+    pub fn from_mono(display: &Display3in7) -> Self {
+        let size = Size::new(HEIGHT, WIDTH);
+        let buffer = display.buffer();
+
+        let pixels = points(size)
+            .map(|point| {
+                // Rotate270 maps (x, y) to (y, HEIGHT - 1 - x) in the buffer, white bits set
+                let (nx, ny) = (point.y as usize, (HEIGHT - 1) as usize - point.x as usize);
+                let white = buffer[nx / 8 + ny * ROW_BYTES] & (0x80 >> (nx % 8)) != 0;
+                if white { Gray2::WHITE } else { Gray2::BLACK }
+            })
+            .collect();
+
+        Self { size, pixels }
+    }
+
+    /// Read back a grayscale display
+    pub fn from_gray(display: &Gray4Display) -> Self {
+        let size = display.size();
+        let pixels = points(size)
+            .map(|point| display.pixel(point).unwrap_or(Gray2::WHITE))
+            .collect();
+
+        Self { size, pixels }
+    }
+
+    /// Read a frame written by `write_png`
+    pub fn read_png(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let decoder = png::Decoder::new(BufReader::new(File::open(path)?));
+        let mut reader = decoder.read_info()?;
+        let mut data = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut data)?;
+        if info.color_type != png::ColorType::Grayscale || info.bit_depth != png::BitDepth::Eight {
+            return Err("Expected an 8-bit grayscale PNG".into());
+        }
+
+        let pixels = data[..info.buffer_size()]
+            .iter()
+            .map(|luma| Gray2::new(luma / 85))
+            .collect();
+
+        Ok(Self {
+            size: Size::new(info.width, info.height),
+            pixels,
+        })
+    }
+
+    /// Number of pixels that differ from the other frame, all of them if the sizes differ
+    pub fn differences(&self, other: &Frame) -> usize {
+        if self.size != other.size {
+            return self.pixels.len().max(other.pixels.len());
+        }
+
+        self.pixels
+            .iter()
+            .zip(other.pixels.iter())
+            .filter(|(a, b)| a != b)
+            .count()
+    }
+
+    /// Write the frame as an 8-bit grayscale PNG
+    pub fn write_png(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+        let file = File::create(path)?;
+        let mut encoder =
+            png::Encoder::new(BufWriter::new(file), self.size.width, self.size.height);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let data: Vec<u8> = self.pixels.iter().map(|pixel| pixel.luma() * 85).collect();
+        encoder.write_header()?.write_image_data(&data)?;
+
+        Ok(())
+    }
+
+    /// Show the frame in a window, until it is closed
+    #[cfg(feature = "window")]
+    pub fn show(&self) {
+        use embedded_graphics_simulator::{OutputSettingsBuilder, SimulatorDisplay, Window};
+
+        let mut display = SimulatorDisplay::<Gray2>::new(self.size);
+        let pixels = points(self.size)
+            .zip(self.pixels.iter())
+            .map(|(point, color)| Pixel(point, *color));
+        display.draw_iter(pixels).ok();
+
+        let settings = OutputSettingsBuilder::new().scale(2).build();
+        Window::new("London Pi Tube", &settings).show_static(&display);
+    }
+
+    #[cfg(not(feature = "window"))]
+    pub fn show(&self) {
+        eprintln!("Built without the window feature, run with --features window");
+    }
+}
+
+/// A monochrome display of any size, for the panels other than the 3.7"
+pub struct Canvas {
+    frame: Frame,
+}
+
+impl Canvas {
+    pub fn new(size: Size) -> Self {
+        let pixels = vec![Gray2::WHITE; size.width as usize * size.height as usize];
+        Self {
+            frame: Frame { size, pixels },
+        }
+    }
+
+    pub fn into_frame(self) -> Frame {
+        self.frame
+    }
+}
+
+impl OriginDimensions for Canvas {
+    fn size(&self) -> Size {
+        self.frame.size
+    }
+}
+
+impl DrawTarget for Canvas {
+    type Color = Color;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let size = self.frame.size;
+        for Pixel(point, color) in pixels {
+            if point.x < 0
+                || point.y < 0
+                || point.x as u32 >= size.width
+                || point.y as u32 >= size.height
+            {
+                continue;
+            }
+
+            self.frame.pixels[point.x as usize + point.y as usize * size.width as usize] =
+                match color {
+                    Color::White => Gray2::WHITE,
+                    Color::Black => Gray2::BLACK,
+                };
+        }
+        Ok(())
+    }
+}
+
+impl SmoothEdges for Canvas {}
+
+/// Points of the frame, row by row
+fn points(size: Size) -> impl Iterator<Item = Point> {
+    (0..size.height as i32).flat_map(move |y| (0..size.width as i32).map(move |x| Point::new(x, y)))
+}
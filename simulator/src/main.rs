@@ -0,0 +1,412 @@
+//! Host display simulator
+//!
+//! Runs the firmware drawing code on the host, drawing into the same
+//! `Display3in7` buffer (or grayscale buffer) as the firmware, at its 480x280
+//! resolution and rotation, then writes the screen to a PNG, or shows it in a
+//! window. Layout changes can then be checked without flashing the Pico.
+//!
+//! The firmware modules are included by path, so only those free of the
+//! embassy runtime can be used here, see `tasks/display/draw.rs`.
+//!
+//! Usage:
+//!
+//! cargo run -- [--fixture <name> | --arrivals <file> | --splash | --panic <message> |
+//!               --state <name> | --boot <name>] [--sleep] [--status <line status>] [--time <HH:MM:SS>] [--gray]
+//!              [--theme <theme>] [--scheme <scheme>] [--panel <panel>] [--out <file>] [--window]
+//!
+//! - `--fixture`: one of the fixtures, see `fixtures.rs` (default "arrivals")
+//! - `--arrivals`: a response saved from the TFL arrivals API
+//! - `--state`: one of the system states, see `fixtures.rs`
+//! - `--boot`: one of the steps of the boot progress checklist, see `fixtures.rs`
+//! - `--sleep`: draw the update as the sleep screen of the inactive hours
+//! - `--time`: the last updated time, omit for a clock not yet synced
+//! - `--gray`: render as a full refresh in four level grayscale
+//! - `--theme`: layout of the update, "classic", "dot-matrix" or "large-type" (default "classic")
+//! - `--scheme`: colors of the screens, "light" or "dark" (default "light")
+//! - `--panel`: draw for another panel, one of "2in9", "4in2" or "7in5", at its
+//!   resolution (default "3in7")
+//! - `--window`: also show the screen in a window, with the `window` feature
+//!
+//! cargo run -- --check | --bless
+//!
+//! Compares every screen against the golden image snapshots, or re-blesses
+//! them, see `snapshots.rs`.
+//!
+use std::error::Error;
+use std::process::ExitCode;
+
+use embedded_graphics::prelude::*;
+use epd_waveshare::color::Color;
+use epd_waveshare::epd3in7::Display3in7;
+use epd_waveshare::prelude::DisplayRotation;
+
+mod fixtures;
+mod frame;
+mod snapshots;
+#[cfg(test)]
+mod tests;
+
+// Shared with the firmware, so only partly used here
+#[allow(dead_code)]
+#[path = "../../config.template.rs"]
+mod config;
+
+#[allow(dead_code)]
+#[path = "../../src/filters"]
+mod filters {
+    pub mod names;
+
+    // Only used by the tests
+    #[cfg(test)]
+    pub mod smoothing;
+    #[cfg(test)]
+    pub mod tracker;
+}
+
+#[allow(dead_code)]
+#[path = "../../src/models"]
+mod models {
+    mod sizes;
+
+    pub use sizes::*;
+
+    pub mod boot;
+    pub mod bounded;
+    pub mod prediction;
+    pub mod system;
+    pub mod update;
+
+    // Only used by the tests
+    #[cfg(test)]
+    pub mod status;
+    #[cfg(test)]
+    pub mod wire;
+}
+
+#[allow(dead_code)]
+#[path = "../../src/tasks"]
+mod tasks {
+    pub mod display {
+        pub mod dot_matrix;
+        pub mod draw;
+        pub mod fit;
+        pub mod gray4;
+        pub mod large_type;
+        pub mod layout;
+        pub mod qr;
+        pub mod refresh;
+        pub mod strings;
+        pub mod typeface;
+    }
+
+    // Only used by the tests
+    #[cfg(test)]
+    pub mod request {
+        pub mod url;
+    }
+}
+
+use config::{StringsConfig, TroubleshootingConfig};
+use frame::{Canvas, Frame};
+use models::boot::BootProgress;
+use models::system::SystemState;
+use models::update::Update;
+use tasks::display::draw::{
+    ColorScheme, DisplayStyles, Sleep, Theme, draw_boot, draw_panic, draw_sleep, draw_splash,
+    draw_state, draw_update,
+};
+use tasks::display::gray4::{self, Gray4Display, SmoothEdges};
+use tasks::display::layout::Scale;
+use tasks::display::refresh::Refresh;
+use tasks::display::strings::Strings;
+
+// As set by the firmware
+const ROTATION: DisplayRotation = DisplayRotation::Rotate270;
+
+// Inactive hours of the sleep screen, as of the config template
+const RESUMES_AT: &str = "06:30";
+// Saturday 25 October, as (month, day, weekday)
+const SLEEP_DATE: (u32, u32, u32) = (10, 25, 6);
+
+// Landscape resolution of the other panels, see the firmware `panel` features
+const PANELS: &[(&str, Size)] = &[
+    ("2in9", Size::new(296, 128)),
+    ("4in2", Size::new(400, 300)),
+    ("7in5", Size::new(800, 480)),
+];
+
+/// The screen to draw
+pub enum Screen {
+    Splash,
+    Update(Box<Update>),
+    Sleep(Box<Update>),
+    State(SystemState),
+    Boot(BootProgress),
+    Panic(String),
+}
+
+/// How the screens are drawn, as configured in the firmware
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Appearance {
+    // Render as a full refresh in four level grayscale
+    pub gray: bool,
+    pub theme: Theme,
+    pub scheme: ColorScheme,
+}
+
+/// What to do with the drawn screens
+enum Mode {
+    Render,
+    Check,
+    Bless,
+}
+
+struct Options {
+    mode: Mode,
+    screen: Screen,
+    updated_at: Option<String>,
+    appearance: Appearance,
+    // Another panel than the 3.7"
+    panel: Option<Size>,
+    out: String,
+    window: bool,
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match options.mode {
+        Mode::Render => {}
+        Mode::Check => {
+            return match snapshots::check(&snapshots::directory()) {
+                Ok(true) => ExitCode::SUCCESS,
+                Ok(false) => ExitCode::FAILURE,
+                Err(e) => {
+                    eprintln!("Failed to check snapshots: {}", e);
+                    ExitCode::FAILURE
+                }
+            };
+        }
+        Mode::Bless => {
+            return match snapshots::bless(&snapshots::directory()) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(e) => {
+                    eprintln!("Failed to bless snapshots: {}", e);
+                    ExitCode::FAILURE
+                }
+            };
+        }
+    }
+
+    let frame = match options.panel {
+        Some(size) => render_panel(
+            &options.screen,
+            options.updated_at.as_deref(),
+            options.appearance,
+            size,
+        ),
+        None => render(
+            &options.screen,
+            options.updated_at.as_deref(),
+            options.appearance,
+        ),
+    };
+
+    if let Err(e) = frame.write_png(&options.out) {
+        eprintln!("Failed to write {}: {}", options.out, e);
+        return ExitCode::FAILURE;
+    }
+    println!("Wrote {}", options.out);
+
+    if options.window {
+        frame.show();
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Draw the screen, as the firmware would for a full refresh of the 3.7" panel
+pub fn render(screen: &Screen, updated_at: Option<&str>, appearance: Appearance) -> Frame {
+    // Only the splash and update screens are drawn in grayscale
+    if appearance.gray && matches!(screen, Screen::Splash | Screen::Update(_)) {
+        let mut display = Box::new(Gray4Display::new());
+        display.set_rotation(gray4::ROTATION);
+        let styles = DisplayStyles::gray(
+            Scale::of(display.size()),
+            appearance.theme,
+            appearance.scheme,
+            strings(),
+        );
+        draw(display.as_mut(), &styles, screen, updated_at);
+        return Frame::from_gray(&display);
+    }
+
+    let mut display = Box::new(Display3in7::default());
+    display.set_rotation(ROTATION);
+    draw_mono(display.as_mut(), screen, updated_at, appearance);
+    Frame::from_mono(&display)
+}
+
+/// Draw the screen for another panel, at its resolution, only ever in monochrome
+pub fn render_panel(
+    screen: &Screen,
+    updated_at: Option<&str>,
+    appearance: Appearance,
+    size: Size,
+) -> Frame {
+    let mut canvas = Canvas::new(size);
+    draw_mono(&mut canvas, screen, updated_at, appearance);
+    canvas.into_frame()
+}
+
+fn draw_mono<D>(display: &mut D, screen: &Screen, updated_at: Option<&str>, appearance: Appearance)
+where
+    D: DrawTarget<Color = Color> + SmoothEdges,
+{
+    let styles = DisplayStyles::new(
+        Scale::of(display.bounding_box().size),
+        appearance.theme,
+        appearance.scheme,
+        strings(),
+    );
+    draw(display, &styles, screen, updated_at);
+}
+
+/// The strings of the language selected by cargo feature, as set by the firmware
+fn strings() -> Strings {
+    Strings::new(StringsConfig::new())
+}
+
+fn draw<D>(
+    display: &mut D,
+    styles: &DisplayStyles<D::Color>,
+    screen: &Screen,
+    updated_at: Option<&str>,
+) where
+    D: DrawTarget + SmoothEdges,
+{
+    let result = match screen {
+        Screen::Splash => draw_splash(display, styles),
+        Screen::Update(update) => draw_update(display, styles, update, Refresh::Full, updated_at),
+        Screen::Sleep(update) => {
+            let sleep = Sleep {
+                resumes_at: RESUMES_AT,
+                date: updated_at.map(|_| SLEEP_DATE),
+                slept_at: updated_at,
+            };
+            draw_sleep(display, styles, update, &sleep)
+        }
+        Screen::State(state) => {
+            let link = state.troubleshooting_link(TroubleshootingConfig::new().page);
+            draw_state(display, styles, *state, link.as_deref())
+        }
+        Screen::Boot(progress) => draw_boot(display, styles, *progress),
+        Screen::Panic(message) => draw_panic(display, styles, message),
+    };
+    if let Err(e) = result {
+        eprintln!("Failed to draw: {:?}", e);
+    }
+}
+
+fn parse_args() -> Result<Options, Box<dyn Error>> {
+    let mut options = Options {
+        mode: Mode::Render,
+        screen: Screen::Splash,
+        updated_at: None,
+        appearance: Appearance::default(),
+        panel: None,
+        out: String::from("screen.png"),
+        window: false,
+    };
+    let mut fixture = String::from("arrivals");
+    let mut arrivals = None;
+    let mut status = String::from("Good Service");
+    let mut screen = None;
+    let mut sleep = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("Missing value for {}", arg));
+        match arg.as_str() {
+            "--fixture" => fixture = value()?,
+            "--arrivals" => arrivals = Some(value()?),
+            "--status" => status = value()?,
+            "--time" => options.updated_at = Some(value()?),
+            "--out" => options.out = value()?,
+            "--splash" => screen = Some(Screen::Splash),
+            "--panic" => screen = Some(Screen::Panic(value()?)),
+            "--state" => {
+                let state = value()?;
+                let (_, state) = fixtures::STATES
+                    .iter()
+                    .find(|(name, _)| *name == state)
+                    .ok_or(format!("Unknown state {}", state))?;
+                screen = Some(Screen::State(*state));
+            }
+            "--boot" => {
+                let progress = value()?;
+                let (_, progress) = fixtures::BOOT_PROGRESS
+                    .iter()
+                    .find(|(name, _)| *name == progress)
+                    .ok_or(format!("Unknown boot progress {}", progress))?;
+                screen = Some(Screen::Boot(*progress));
+            }
+            "--sleep" => sleep = true,
+            "--gray" => options.appearance.gray = true,
+            "--theme" => {
+                options.appearance.theme = match value()?.as_str() {
+                    "classic" => Theme::Classic,
+                    "dot-matrix" => Theme::DotMatrix,
+                    "large-type" => Theme::LargeType,
+                    theme => return Err(format!("Unknown theme {}", theme).into()),
+                };
+            }
+            "--scheme" => {
+                options.appearance.scheme = match value()?.as_str() {
+                    "light" => ColorScheme::Light,
+                    "dark" => ColorScheme::Dark,
+                    scheme => return Err(format!("Unknown color scheme {}", scheme).into()),
+                };
+            }
+            "--panel" => {
+                let panel = value()?;
+                options.panel = match PANELS.iter().find(|(name, _)| *name == panel) {
+                    Some((_, size)) => Some(*size),
+                    None if panel == "3in7" => None,
+                    None => return Err(format!("Unknown panel {}", panel).into()),
+                };
+            }
+            "--window" => options.window = true,
+            "--check" => options.mode = Mode::Check,
+            "--bless" => options.mode = Mode::Bless,
+            _ => return Err(format!("Unknown argument {}", arg).into()),
+        }
+    }
+
+    let update = match (&screen, arrivals) {
+        (Some(_), _) => None,
+        (None, Some(path)) => {
+            let json = std::fs::read_to_string(&path)?;
+            Some(fixtures::from_arrivals(&json, &status)?)
+        }
+        (None, None) => Some(fixtures::fixture(&fixture).ok_or(format!(
+            "Unknown fixture {}, expected one of {:?}",
+            fixture,
+            fixtures::FIXTURES
+        ))?),
+    };
+    options.screen = match (screen, update) {
+        (_, Some(update)) if sleep => Screen::Sleep(Box::new(update)),
+        (_, Some(update)) => Screen::Update(Box::new(update)),
+        (Some(screen), None) => screen,
+        (None, None) => Screen::Splash,
+    };
+
+    Ok(options)
+}
@@ -0,0 +1,288 @@
+//! Golden image snapshots
+//!
+//! Draws each screen of a matrix of fixtures, and compares it pixel for pixel
+//! against the reference PNG checked in to `snapshots/`, so that regressions
+//! in the layout are caught without the hardware.
+//!
+//! - `--check`: compare each screen, writing `<name>.actual.png` for any that
+//!   differ, and fail if any do
+//! - `--bless`: overwrite the references with the screens as now drawn, once
+//!   the differences have been checked to be intended
+//!
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use crate::frame::Frame;
+use crate::models::system::SystemState;
+use crate::tasks::display::draw::{ColorScheme, Theme};
+use crate::{Appearance, Screen, fixtures, render};
+
+// Last updated time of the snapshots, unless the clock is not synced
+const UPDATED_AT: Option<&str> = Some("12:34:56");
+
+// Monochrome, in the dot-matrix theme
+const DOT_MATRIX: Appearance = Appearance {
+    gray: false,
+    theme: Theme::DotMatrix,
+    scheme: ColorScheme::Light,
+};
+
+// Monochrome, only the next train
+const LARGE_TYPE: Appearance = Appearance {
+    gray: false,
+    theme: Theme::LargeType,
+    scheme: ColorScheme::Light,
+};
+
+// Monochrome, inverted
+const DARK: Appearance = Appearance {
+    gray: false,
+    theme: Theme::Classic,
+    scheme: ColorScheme::Dark,
+};
+
+/// A screen to snapshot
+struct Case {
+    name: &'static str,
+    screen: Screen,
+    updated_at: Option<&'static str>,
+    appearance: Appearance,
+}
+
+fn cases() -> Vec<Case> {
+    let update = |name| Screen::Update(Box::new(fixtures::fixture(name).unwrap()));
+
+    let mut cases = vec![Case {
+        name: "splash",
+        screen: Screen::Splash,
+        updated_at: UPDATED_AT,
+        appearance: Appearance::default(),
+    }];
+    cases.extend(fixtures::FIXTURES.iter().map(|name| Case {
+        name,
+        screen: update(name),
+        updated_at: UPDATED_AT,
+        appearance: Appearance::default(),
+    }));
+    cases.extend(fixtures::BOOT_PROGRESS.iter().map(|(name, progress)| Case {
+        name,
+        screen: Screen::Boot(*progress),
+        updated_at: None,
+        appearance: Appearance::default(),
+    }));
+    cases.extend(fixtures::STATES.iter().map(|(name, state)| Case {
+        name,
+        screen: Screen::State(*state),
+        updated_at: None,
+        appearance: Appearance::default(),
+    }));
+    cases.extend([
+        Case {
+            name: "clock-not-synced",
+            screen: update("arrivals"),
+            updated_at: None,
+            appearance: Appearance::default(),
+        },
+        Case {
+            name: "arrivals-gray",
+            screen: update("arrivals"),
+            updated_at: UPDATED_AT,
+            appearance: Appearance {
+                gray: true,
+                ..Appearance::default()
+            },
+        },
+        Case {
+            name: "arrivals-dot-matrix",
+            screen: update("arrivals"),
+            updated_at: UPDATED_AT,
+            appearance: DOT_MATRIX,
+        },
+        Case {
+            // An odd minute, showing the line status in place of the current location
+            name: "severe-delays-dot-matrix",
+            screen: update("severe-delays"),
+            updated_at: Some("12:35:56"),
+            appearance: DOT_MATRIX,
+        },
+        Case {
+            name: "many-arrivals-dot-matrix",
+            screen: update("many-arrivals"),
+            updated_at: UPDATED_AT,
+            appearance: DOT_MATRIX,
+        },
+        Case {
+            name: "fault-dot-matrix",
+            screen: update("fault"),
+            updated_at: UPDATED_AT,
+            appearance: DOT_MATRIX,
+        },
+        Case {
+            name: "arrivals-dot-matrix-gray",
+            screen: update("arrivals"),
+            updated_at: UPDATED_AT,
+            appearance: Appearance {
+                gray: true,
+                ..DOT_MATRIX
+            },
+        },
+        Case {
+            name: "arrivals-large-type",
+            screen: update("arrivals"),
+            updated_at: UPDATED_AT,
+            appearance: LARGE_TYPE,
+        },
+        Case {
+            name: "long-names-large-type",
+            screen: update("long-names"),
+            updated_at: UPDATED_AT,
+            appearance: LARGE_TYPE,
+        },
+        Case {
+            name: "empty-large-type",
+            screen: update("empty"),
+            updated_at: UPDATED_AT,
+            appearance: LARGE_TYPE,
+        },
+        Case {
+            name: "fault-large-type",
+            screen: update("fault"),
+            updated_at: UPDATED_AT,
+            appearance: LARGE_TYPE,
+        },
+        Case {
+            name: "arrivals-large-type-gray",
+            screen: update("arrivals"),
+            updated_at: UPDATED_AT,
+            appearance: Appearance {
+                gray: true,
+                ..LARGE_TYPE
+            },
+        },
+        Case {
+            name: "splash-dark",
+            screen: Screen::Splash,
+            updated_at: UPDATED_AT,
+            appearance: DARK,
+        },
+        Case {
+            name: "arrivals-dark",
+            screen: update("arrivals"),
+            updated_at: UPDATED_AT,
+            appearance: DARK,
+        },
+        Case {
+            name: "arrivals-dark-gray",
+            screen: update("arrivals"),
+            updated_at: UPDATED_AT,
+            appearance: Appearance { gray: true, ..DARK },
+        },
+        Case {
+            name: "arrivals-dot-matrix-dark",
+            screen: update("arrivals"),
+            updated_at: UPDATED_AT,
+            appearance: Appearance {
+                theme: Theme::DotMatrix,
+                ..DARK
+            },
+        },
+        Case {
+            name: "auth-failed-dark",
+            screen: Screen::State(SystemState::AuthFailed { status: 401 }),
+            updated_at: None,
+            appearance: DARK,
+        },
+        Case {
+            name: "sleep-dark",
+            screen: Screen::Sleep(Box::new(fixtures::fixture("planned-closures").unwrap())),
+            updated_at: UPDATED_AT,
+            appearance: DARK,
+        },
+        Case {
+            name: "sleep",
+            screen: Screen::Sleep(Box::new(fixtures::fixture("planned-closures").unwrap())),
+            updated_at: UPDATED_AT,
+            appearance: Appearance::default(),
+        },
+        Case {
+            name: "sleep-clock-not-synced",
+            screen: Screen::Sleep(Box::new(fixtures::fixture("arrivals").unwrap())),
+            updated_at: None,
+            appearance: Appearance::default(),
+        },
+        Case {
+            name: "panic",
+            screen: Screen::Panic(String::from(
+                "Panic at src/main.rs:1:1\nExample panic message",
+            )),
+            updated_at: None,
+            appearance: Appearance::default(),
+        },
+        Case {
+            name: "panic-dark",
+            screen: Screen::Panic(String::from(
+                "Panic at src/main.rs:1:1\nExample panic message",
+            )),
+            updated_at: None,
+            appearance: DARK,
+        },
+    ]);
+    cases
+}
+
+/// Directory of the reference PNGs
+pub fn directory() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("snapshots")
+}
+
+/// Compare each screen against its reference, returning whether all match
+pub fn check(dir: &Path) -> Result<bool, Box<dyn Error>> {
+    let mut passed = true;
+
+    for case in cases() {
+        let frame = render(&case.screen, case.updated_at, case.appearance);
+        let reference = dir.join(format!("{}.png", case.name));
+        let actual = dir.join(format!("{}.actual.png", case.name));
+
+        let differences = match Frame::read_png(&reference) {
+            Ok(expected) => frame.differences(&expected),
+            Err(e) => {
+                println!("MISSING {}: {}", case.name, e);
+                passed = false;
+                continue;
+            }
+        };
+
+        if differences == 0 {
+            println!("ok      {}", case.name);
+            // Remove any left from a previous failure
+            let _ = std::fs::remove_file(&actual);
+        } else {
+            println!("FAILED  {}: {} pixels differ", case.name, differences);
+            frame.write_png(&actual)?;
+            passed = false;
+        }
+    }
+
+    if !passed {
+        println!(
+            "Compare the *.actual.png in {} against the references, then run with --bless if the changes are intended",
+            dir.display()
+        );
+    }
+    Ok(passed)
+}
+
+/// Overwrite the references with the screens as now drawn
+pub fn bless(dir: &Path) -> Result<(), Box<dyn Error>> {
+    std::fs::create_dir_all(dir)?;
+
+    for case in cases() {
+        let frame = render(&case.screen, case.updated_at, case.appearance);
+        frame.write_png(dir.join(format!("{}.png", case.name)))?;
+        let _ = std::fs::remove_file(dir.join(format!("{}.actual.png", case.name)));
+        println!("blessed {}", case.name);
+    }
+
+    Ok(())
+}
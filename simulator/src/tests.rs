@@ -0,0 +1,16 @@
+//! Host tests
+//!
+//! Tests of the drawing code, and of the firmware modules free of the embassy
+//! runtime, which are included by path as in `main.rs`, so run on the host.
+//!
+mod bounded;
+mod fit;
+mod names;
+mod qr;
+mod smoothing;
+// The snapshots are drawn with the default language and fonts
+#[cfg(not(any(feature = "lang-fr", feature = "typeface")))]
+mod snapshots;
+mod tracker;
+mod url;
+mod wire;
@@ -0,0 +1,73 @@
+//! Truncating bounded string tests
+//!
+//! Values at and either side of the capacity, and capacities too small for
+//! the ellipsis, always cut at a character boundary.
+//!
+use crate::models::bounded::BoundedString;
+
+/// Truncate the value to the capacity, checking it fits
+fn truncate<const N: usize>(s: &str) -> String {
+    let bounded = BoundedString::<N>::truncating(s);
+    assert!(bounded.len() <= N, "{bounded:?} exceeds {N} bytes");
+    bounded.to_string()
+}
+
+#[test]
+fn keeps_values_that_fit() {
+    let cases: &[(&str, &str)] = &[
+        // Exactly the capacity
+        ("Upminst", "Upminst"),
+        ("Upminste", "Upminste"),
+        // Multi-byte characters, exactly the capacity
+        ("Châtele", "Châtele"),
+    ];
+    for (value, expected) in cases {
+        assert_eq!(truncate::<8>(value), *expected, "{value}");
+        assert_eq!(truncate::<9>(value), *value, "{value}");
+    }
+}
+
+#[test]
+fn truncates_values_beyond_the_capacity() {
+    let cases: &[(&str, &str)] = &[
+        // One byte over the capacity
+        ("Upminster", "Upmin..."),
+        ("Heathrow Terminal 5", "Heath..."),
+        // Space left at the end by the cut, which is trimmed
+        ("East Ham", "East Ham"),
+        ("East Hams", "East..."),
+        // Multi-byte character straddling the cut, which is dropped whole
+        ("Upmiñster", "Upmi..."),
+        ("Upm€ster", "Upm..."),
+    ];
+    for (value, expected) in cases {
+        assert_eq!(truncate::<8>(value), *expected, "{value}");
+    }
+}
+
+#[test]
+fn truncates_to_the_ellipsis_below_its_length() {
+    assert_eq!(truncate::<0>("Upminster"), "");
+    assert_eq!(truncate::<1>("Upminster"), ".");
+    assert_eq!(truncate::<2>("Upminster"), "..");
+    assert_eq!(truncate::<3>("Upminster"), "...");
+    assert_eq!(truncate::<4>("Upminster"), "U...");
+    // Values that fit are still kept
+    assert_eq!(truncate::<2>("Up"), "Up");
+}
+
+#[test]
+fn keeps_empty_values() {
+    assert_eq!(truncate::<0>(""), "");
+    assert_eq!(truncate::<8>(""), "");
+    assert!(BoundedString::<8>::new().is_empty());
+}
+
+#[test]
+fn truncates_when_deserialised() {
+    let bounded: BoundedString<8> = serde_json::from_str(r#""Heathrow Terminal 5""#).unwrap();
+    assert_eq!(bounded.as_str(), "Heath...");
+
+    let bounded: BoundedString<8> = serde_json::from_str(r#""Bank""#).unwrap();
+    assert_eq!(bounded.as_str(), "Bank");
+}
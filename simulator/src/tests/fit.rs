@@ -0,0 +1,214 @@
+//! Text fitting tests
+//!
+//! Fits long station, destination and location names into the regions of
+//! the layout they are drawn in, on each panel, with each fit strategy, then
+//! draws them as the firmware would and checks that every pixel drawn lies
+//! inside the region.
+//!
+use embedded_graphics::pixelcolor::Gray2;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use epd_waveshare::color::Color;
+use u8g2_fonts::FontRenderer;
+use u8g2_fonts::types::{FontColor, HorizontalAlignment, VerticalPosition};
+
+use crate::frame::{Canvas, Frame};
+use crate::tasks::display::draw::{ColorScheme, DisplayStyles, Theme};
+use crate::tasks::display::fit::{FIT_MAX_LINES, Fit, fit_text};
+use crate::tasks::display::layout::{FontMetrics, Layout, MARGIN, Rows, Scale};
+use crate::{Appearance, PANELS, Screen, fixtures, render_panel, strings};
+
+// Resolution of the 3.7" panel, in landscape
+const PANEL_3IN7: Size = Size::new(480, 280);
+
+const FITS: [Fit; 4] = [Fit::Ellipsis, Fit::Abbreviate, Fit::StepDown, Fit::Wrap];
+
+// Longer than any region, with and without spaces, with multi-byte characters,
+// and longer than the fitted text holds
+const NAMES: &[&str] = &[
+    "Heathrow Terminals 2 & 3 Underground Station",
+    "High Street Kensington (Circle Line)-Underground",
+    "Cutty Sark (for Maritime Greenwich)",
+    "Shepherd's Bush Market Junction Road North Broadway",
+    "Llanfairpwllgwyngyllgogerychwyrndrobwllllantysiliogogogoch",
+    "Châtelet-les-Halles Saint-Germain-des-Prés Gare du Nord",
+    "Between Earl's Court and West Brompton, then onwards to Fulham Broadway, Parsons Green and Putney Bridge",
+    "Upminster",
+];
+
+/// The names, less those of characters the `typeface` tables are subset without
+fn names() -> impl Iterator<Item = &'static str> {
+    NAMES
+        .iter()
+        .copied()
+        .filter(|name| !cfg!(feature = "typeface") || name.is_ascii())
+}
+
+/// A region of the layout, and the fonts drawn in it, largest first
+struct Region<'a> {
+    name: &'static str,
+    area: Rectangle,
+    fonts: [&'a FontRenderer; 2],
+}
+
+/// The regions of the layout that names are fitted into
+fn regions<'a>(layout: &Layout, styles: &'a DisplayStyles<Color>) -> [Region<'a>; 3] {
+    let header = &layout.header;
+    let arrivals = &layout.arrivals;
+    let column_width = arrivals.size.width - layout.destination_offset as u32;
+
+    [
+        // Title and platform, inside the margins
+        Region {
+            name: "header",
+            area: Rectangle::new(
+                header.top_left + Point::new(MARGIN, MARGIN),
+                Size::new(
+                    header.size.width - 2 * MARGIN as u32,
+                    FontMetrics::of(&styles.header_font).height(FIT_MAX_LINES as i32) as u32,
+                ),
+            ),
+            fonts: [&styles.header_font, &styles.header_small_font],
+        },
+        // Destination and current location, in the column clear of the time
+        Region {
+            name: "destination",
+            area: Rectangle::new(
+                arrivals.top_left + Point::new(layout.destination_offset, 0),
+                Size::new(column_width, arrivals.size.height),
+            ),
+            fonts: [&styles.bold_text_font, &styles.regular_text_font],
+        },
+        Region {
+            name: "location",
+            area: Rectangle::new(
+                arrivals.top_left + Point::new(layout.destination_offset, 0),
+                Size::new(column_width, arrivals.size.height),
+            ),
+            fonts: [&styles.regular_text_font, &styles.tiny_font],
+        },
+    ]
+}
+
+/// Fit the name into the region, and draw it as the firmware, top aligned in lines as a row of
+/// the region, unless too tall for it
+fn draw_fitted(size: Size, region: &Region, name: &str, fit: Fit) -> Frame {
+    let fitted = fit_text(name, region.area.size.width as i32, &region.fonts, fit);
+    let font = region.fonts[fitted.font];
+    let metrics = FontMetrics::of(font);
+
+    let mut canvas = Canvas::new(size);
+    let Some(row) = Rows::new(region.area).next(metrics.height(fitted.lines.len() as i32)) else {
+        return canvas.into_frame();
+    };
+    for (idx, line) in fitted.lines.iter().enumerate() {
+        font.render_aligned(
+            line.as_str(),
+            row.top_left + Point::new(0, metrics.line_height * idx as i32),
+            VerticalPosition::Top,
+            HorizontalAlignment::Left,
+            FontColor::Transparent(Color::Black),
+            &mut canvas,
+        )
+        .unwrap();
+    }
+    canvas.into_frame()
+}
+
+/// Points of the frame drawn outside the area
+fn outside(frame: &Frame, area: &Rectangle) -> Vec<Point> {
+    let width = frame.size.width as usize;
+    frame
+        .pixels
+        .iter()
+        .enumerate()
+        .filter(|(_, pixel)| **pixel != Gray2::WHITE)
+        .map(|(idx, _)| Point::new((idx % width) as i32, (idx / width) as i32))
+        .filter(|point| !area.contains(*point))
+        .collect()
+}
+
+#[test]
+fn fitted_names_stay_inside_their_regions() {
+    let panels = [("3in7", PANEL_3IN7)]
+        .into_iter()
+        .chain(PANELS.iter().copied());
+
+    for (panel, size) in panels {
+        let styles = DisplayStyles::new(
+            Scale::of(size),
+            Theme::Classic,
+            ColorScheme::Light,
+            strings(),
+        );
+        let layout = Layout::new(Rectangle::new(Point::zero(), size), &styles);
+
+        for region in regions(&layout, &styles) {
+            for name in names() {
+                for fit in FITS {
+                    let frame = draw_fitted(size, &region, name, fit);
+                    let outside = outside(&frame, &region.area);
+                    assert!(
+                        outside.is_empty(),
+                        "{name:?} fitted by {fit:?} into the {} of {panel} at {:?} drawn outside at {:?}",
+                        region.name,
+                        region.area,
+                        &outside[..outside.len().min(8)]
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn fitted_names_are_drawn() {
+    // Nothing drawn would pass the above, so each fit must draw something of the name
+    for fit in FITS {
+        let styles = DisplayStyles::new(
+            Scale::Regular,
+            Theme::Classic,
+            ColorScheme::Light,
+            strings(),
+        );
+        let layout = Layout::new(Rectangle::new(Point::zero(), PANEL_3IN7), &styles);
+        for region in regions(&layout, &styles) {
+            for name in names() {
+                let frame = draw_fitted(PANEL_3IN7, &region, name, fit);
+                assert!(
+                    frame.pixels.iter().any(|pixel| *pixel != Gray2::WHITE),
+                    "{name:?} fitted by {fit:?} into the {} drew nothing",
+                    region.name
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn first_arrival_is_drawn_on_every_panel() {
+    // Without its location, where the arrivals region is too short for both
+    let update = fixtures::fixture("arrivals").unwrap();
+    for (panel, size) in PANELS.iter().copied() {
+        let styles = DisplayStyles::new(
+            Scale::of(size),
+            Theme::Classic,
+            ColorScheme::Light,
+            strings(),
+        );
+        let layout = Layout::new(Rectangle::new(Point::zero(), size), &styles);
+        let frame = render_panel(
+            &Screen::Update(Box::new(update.clone())),
+            None,
+            Appearance::default(),
+            size,
+        );
+        let width = size.width as usize;
+        assert!(
+            layout.arrivals.points().any(|point| {
+                frame.pixels[point.x as usize + point.y as usize * width] != Gray2::WHITE
+            }),
+            "no arrivals drawn on {panel}"
+        );
+    }
+}
@@ -0,0 +1,136 @@
+//! Station name normalisation tests
+//!
+//! Names as returned by the TFL API, normalised with the compiled in tables
+//! alone, and with user overrides from the config.
+//!
+use crate::config::NamesConfig;
+use crate::filters::names::NameNormaliser;
+use crate::fixtures::prediction;
+use crate::models::TFL_API_FIELD_LONG_STR_SIZE;
+use crate::models::bounded::BoundedString;
+use crate::models::prediction::{ARRAY_MAX_SIZE_PREDICTION_MODEL, Prediction};
+
+/// Normalise each name, as (name from the API, name expected)
+fn check(config: NamesConfig, cases: &[(&str, &str)]) {
+    let normaliser = NameNormaliser::new(config);
+    for (name, expected) in cases {
+        let name = BoundedString::<TFL_API_FIELD_LONG_STR_SIZE>::truncating(name);
+        assert_eq!(normaliser.normalise(&name).as_str(), *expected, "{name}");
+    }
+}
+
+#[test]
+fn strips_suffixes() {
+    check(
+        NamesConfig { overrides: &[] },
+        &[
+            ("Upminster-Underground", "Upminster"),
+            ("East Putney Underground Station", "East Putney"),
+            ("Paddington (H&C Line)-Underground", "Paddington"),
+            (
+                "Edgware Road (Circle Line) Underground Station",
+                "Edgware Road",
+            ),
+            ("Hammersmith (H&C Line)", "Hammersmith"),
+            ("Stratford Rail Station", "Stratford"),
+            ("Bank DLR Station", "Bank"),
+            ("Clapham Junction Overground Station", "Clapham Junction"),
+            ("Wimbledon Station", "Wimbledon"),
+            // Suffixes within a name, and trailing spaces
+            ("Bank-Underground / Monument-Underground", "Bank / Monument"),
+            ("Richmond Station  ", "Richmond"),
+        ],
+    );
+}
+
+#[test]
+fn abbreviates() {
+    check(
+        NamesConfig { overrides: &[] },
+        &[
+            ("Kensington (Olympia) Underground Station", "Olympia"),
+            (
+                "Heathrow Terminals 2 & 3 Underground Station",
+                "Heathrow T2&3",
+            ),
+            ("Heathrow Terminal 5", "Heathrow T5"),
+            (
+                "King's Cross St. Pancras Underground Station",
+                "King's Cross",
+            ),
+            ("High Street Kensington-Underground", "High St Kensington"),
+        ],
+    );
+}
+
+#[test]
+fn leaves_other_names_unchanged() {
+    check(
+        NamesConfig { overrides: &[] },
+        &[
+            ("Upminster", "Upminster"),
+            ("Earl's Court", "Earl's Court"),
+            // Only line qualifiers, at the end, are removed
+            ("Shepherd's Bush (Central)", "Shepherd's Bush (Central)"),
+            ("Station Road", "Station Road"),
+            (
+                "Between Barking and East Ham",
+                "Between Barking and East Ham",
+            ),
+            ("At Platform", "At Platform"),
+            ("", ""),
+        ],
+    );
+}
+
+#[test]
+fn overrides_take_precedence() {
+    check(
+        NamesConfig {
+            overrides: &[
+                // The raw name
+                ("Upminster-Underground", "Upminster (End)"),
+                // The stripped name, over the compiled in abbreviation
+                ("Kensington (Olympia)", "Kensington Olympia"),
+                ("East Putney", "E Putney"),
+            ],
+        },
+        &[
+            ("Upminster-Underground", "Upminster (End)"),
+            ("Upminster Underground Station", "Upminster"),
+            (
+                "Kensington (Olympia) Underground Station",
+                "Kensington Olympia",
+            ),
+            ("Kensington (Olympia)", "Kensington Olympia"),
+            ("East Putney Underground Station", "E Putney"),
+            // Unaffected
+            ("Heathrow Terminal 4", "Heathrow T4"),
+        ],
+    );
+}
+
+#[test]
+fn normalises_each_prediction() {
+    let mut predictions: heapless::Vec<Prediction, ARRAY_MAX_SIZE_PREDICTION_MODEL> =
+        [Prediction {
+            station_name: BoundedString::truncating("East Putney Underground Station"),
+            ..prediction(
+                1,
+                "Upminster Underground Station",
+                "At Kensington (Olympia)",
+                60,
+            )
+        }]
+        .into_iter()
+        .collect();
+
+    NameNormaliser::new(NamesConfig { overrides: &[] }).process(&mut predictions);
+    assert_eq!(predictions[0].station_name.as_str(), "East Putney");
+    assert_eq!(predictions[0].destination_name.as_str(), "Upminster");
+    // Locations are only normalised as a whole
+    assert_eq!(
+        predictions[0].current_location.as_str(),
+        "At Kensington (Olympia)"
+    );
+}
@@ -0,0 +1,204 @@
+//! QR code tests
+//!
+//! Draws the screens and QR codes as the firmware would, writes them to PNGs
+//! and reads them back, then decodes the QR codes with `rqrr`, an independent
+//! reader, to check that they scan, and hold the expected data.
+//!
+use embedded_graphics::prelude::*;
+use epd_waveshare::color::Color;
+
+use crate::config::TroubleshootingConfig;
+use crate::frame::{Canvas, Frame};
+use crate::tasks::display::draw::ColorScheme;
+use crate::tasks::display::layout::MARGIN;
+use crate::tasks::display::qr::{Ecc, QUIET_ZONE, QrCode, QrError};
+use crate::{Appearance, PANELS, Screen, fixtures, render, render_panel};
+
+// Longest panic message, as of the firmware panic handler
+const MAX_PANIC_MESSAGE_LEN: usize = 200;
+
+/// Write the frame to a PNG, and read it back
+fn round_trip(frame: &Frame, name: &str) -> Frame {
+    let path = std::env::temp_dir().join(format!(
+        "london-pi-tube-{}-{}.png",
+        name,
+        std::process::id()
+    ));
+    frame.write_png(&path).unwrap();
+    let read = Frame::read_png(&path);
+    let _ = std::fs::remove_file(&path);
+    read.unwrap()
+}
+
+/// Decode each QR code in the frame
+fn decode(frame: &Frame) -> Vec<String> {
+    let width = frame.size.width as usize;
+    let mut image =
+        rqrr::PreparedImage::prepare_from_greyscale(width, frame.size.height as usize, |x, y| {
+            frame.pixels[x + y * width].luma() * 85
+        });
+    image
+        .detect_grids()
+        .iter()
+        .map(|grid| grid.decode().expect("Failed to decode QR code").1)
+        .collect()
+}
+
+#[test]
+fn state_screens_link_to_troubleshooting() {
+    for (name, state) in fixtures::STATES {
+        let link = state
+            .troubleshooting_link(TroubleshootingConfig::new().page)
+            .unwrap();
+        let frame = round_trip(
+            &render(&Screen::State(*state), None, Appearance::default()),
+            name,
+        );
+        assert_eq!(decode(&frame), [link.as_str()], "{}", name);
+    }
+}
+
+#[test]
+fn state_screens_link_to_troubleshooting_in_dark_scheme() {
+    // Still dark on light, as not every reader scans inverted codes
+    let appearance = Appearance {
+        scheme: ColorScheme::Dark,
+        ..Appearance::default()
+    };
+    for (name, state) in fixtures::STATES {
+        let link = state
+            .troubleshooting_link(TroubleshootingConfig::new().page)
+            .unwrap();
+        let frame = round_trip(
+            &render(&Screen::State(*state), None, appearance),
+            &format!("{}-dark", name),
+        );
+        assert_eq!(decode(&frame), [link.as_str()], "{}", name);
+    }
+}
+
+#[test]
+fn state_screens_link_to_troubleshooting_on_larger_panels() {
+    let (name, state) = fixtures::STATES[0];
+    let link = state
+        .troubleshooting_link(TroubleshootingConfig::new().page)
+        .unwrap();
+    for (panel, size) in PANELS.iter().filter(|(_, size)| size.height >= 280) {
+        let frame = round_trip(
+            &render_panel(&Screen::State(state), None, Appearance::default(), *size),
+            &format!("{}-{}", name, panel),
+        );
+        assert_eq!(decode(&frame), [link.as_str()], "{}", panel);
+    }
+}
+
+#[test]
+fn state_screens_omit_unreadable_qr_codes() {
+    // Too small to be read from the 2.9" panel
+    let (name, state) = fixtures::STATES[0];
+    let frame = round_trip(
+        &render_panel(
+            &Screen::State(state),
+            None,
+            Appearance::default(),
+            Size::new(296, 128),
+        ),
+        &format!("{}-2in9", name),
+    );
+    assert!(decode(&frame).is_empty());
+}
+
+/// A panic message of the longest length
+fn longest_panic_message() -> String {
+    let mut message = String::from("Panic at src/tasks/request.rs:123:45\n");
+    while message.len() < MAX_PANIC_MESSAGE_LEN {
+        message += "called `Result::unwrap()` on an `Err` value ";
+    }
+    message.truncate(MAX_PANIC_MESSAGE_LEN);
+    message
+}
+
+#[test]
+fn panic_screen_encodes_report() {
+    let message = longest_panic_message();
+    let frame = round_trip(
+        &render(&Screen::Panic(message.clone()), None, Appearance::default()),
+        "panic",
+    );
+    assert_eq!(decode(&frame), [message]);
+}
+
+#[test]
+fn panic_screen_draws_on_narrow_panels() {
+    // Narrower than the margins and the QR code, which is as wide as fits
+    let message = String::from("Panic at src/tasks/request.rs:123:45");
+    for size in [Size::new(80, 200), Size::new(20, 40)]
+        .into_iter()
+        .chain(PANELS.iter().map(|(_, size)| *size))
+    {
+        render_panel(
+            &Screen::Panic(message.clone()),
+            None,
+            Appearance::default(),
+            size,
+        );
+    }
+}
+
+#[test]
+fn panic_screen_stays_within_margins() {
+    // Wrapped until the 2.9" panel is full, leaving the bottom margin left of the QR code clear
+    let size = Size::new(296, 128);
+    let frame = render_panel(
+        &Screen::Panic(longest_panic_message()),
+        None,
+        Appearance::default(),
+        size,
+    );
+    let width = size.width as usize;
+    let bg = frame.pixels[0];
+    for y in (size.height as usize - MARGIN as usize)..size.height as usize {
+        for x in 0..width / 2 {
+            assert_eq!(frame.pixels[x + y * width], bg, "({}, {})", x, y);
+        }
+    }
+}
+
+#[test]
+fn qr_codes_decode_in_every_version() {
+    // Longest data of each version, at each error correction level
+    let capacities = [
+        (Ecc::Low, [17, 32, 53, 78, 106, 134, 154, 192, 230, 271]),
+        (Ecc::Medium, [14, 26, 42, 62, 84, 106, 122, 152, 180, 213]),
+    ];
+    for (ecc, capacities) in capacities {
+        for (version, capacity) in (1..).zip(capacities) {
+            let data: String = (0..capacity)
+                .map(|i| char::from(b' ' + (i * 7 % 95) as u8))
+                .collect();
+            let qr = QrCode::encode(data.as_bytes(), ecc).unwrap();
+            assert_eq!(
+                qr.drawn_size(1),
+                version * 4 + 17 + 2 * QUIET_ZONE,
+                "{:?} {}",
+                ecc,
+                capacity
+            );
+
+            let mut canvas = Canvas::new(Size::new_equal(qr.drawn_size(3)));
+            qr.draw(&mut canvas, Point::zero(), 3, Color::Black, Color::White)
+                .unwrap();
+            let frame = round_trip(&canvas.into_frame(), &format!("{:?}-{}", ecc, version));
+            assert_eq!(decode(&frame), [data], "{:?} {}", ecc, capacity);
+        }
+    }
+}
+
+#[test]
+fn qr_codes_too_long_are_rejected() {
+    let data = [b'x'; 272];
+    assert_eq!(
+        QrCode::encode(&data, Ecc::Low).unwrap_err(),
+        QrError::TooLong
+    );
+}
@@ -0,0 +1,208 @@
+//! Countdown smoothing tests
+//!
+//! Replays recorded countdowns of a train, a prediction each request cycle,
+//! through the smoother as the request task would, and checks the countdowns
+//! shown, with the default and the extreme configs.
+//!
+use heapless::Vec;
+
+use crate::config::SmoothingConfig;
+use crate::filters::smoothing::CountdownSmoother;
+use crate::filters::tracker::VehicleTracker;
+use crate::fixtures::prediction;
+use crate::models::bounded::BoundedString;
+use crate::models::prediction::{ARRAY_MAX_SIZE_PREDICTION_MODEL, Prediction};
+
+// Time between the request cycles
+const CYCLE_SECS: u32 = 30;
+
+/// Countdowns predicted, `None` where missing from the response, and the countdowns shown
+type Replay<'a> = (&'a [Option<u32>], &'a [Option<u32>]);
+
+/// The response of a request cycle, with the train if not missing
+fn response(countdown: Option<u32>) -> Vec<Prediction, ARRAY_MAX_SIZE_PREDICTION_MODEL> {
+    countdown
+        .map(|countdown| prediction(1, "Upminster", "", countdown))
+        .into_iter()
+        .collect()
+}
+
+/// Replay the countdowns of a train, `None` where missing from the response,
+/// returning the countdowns shown
+fn replay(config: SmoothingConfig, countdowns: &[Option<u32>]) -> std::vec::Vec<Option<u32>> {
+    let mut smoother = CountdownSmoother::new(config);
+    countdowns
+        .iter()
+        .map(|countdown| {
+            let mut predictions = response(*countdown);
+            smoother.process(&mut predictions, CYCLE_SECS);
+            predictions.first().map(|p| p.time_to_station)
+        })
+        .collect()
+}
+
+/// Check each replay
+fn check(config: SmoothingConfig, replays: &[Replay]) {
+    for (predicted, shown) in replays {
+        assert_eq!(replay(config, predicted), *shown, "{predicted:?}");
+    }
+}
+
+/// A config of the maximum increase and responsiveness
+fn config(max_increase_secs: u32, responsiveness_percent: u32) -> SmoothingConfig {
+    SmoothingConfig {
+        max_increase_secs,
+        responsiveness_percent,
+    }
+}
+
+#[test]
+fn clamps_jumps_up() {
+    check(
+        config(60, 50),
+        &[
+            // A single bounce up, held by the clock until the predictions catch up
+            (
+                &[Some(180), Some(400), Some(150), Some(120)],
+                &[Some(180), Some(180), Some(150), Some(120)],
+            ),
+            // A lasting delay, taken up no faster than the maximum increase
+            (
+                &[Some(180), Some(400), Some(400), Some(400)],
+                &[Some(180), Some(180), Some(180), Some(180)],
+            ),
+        ],
+    );
+}
+
+#[test]
+fn converges_on_the_predictions() {
+    check(
+        config(60, 50),
+        &[
+            // The first prediction was too late, the rest count down with the clock
+            (
+                &[
+                    Some(300),
+                    Some(200),
+                    Some(170),
+                    Some(140),
+                    Some(110),
+                    Some(80),
+                    Some(50),
+                ],
+                &[
+                    Some(300),
+                    Some(235),
+                    Some(188),
+                    Some(149),
+                    Some(115),
+                    Some(83),
+                    Some(52),
+                ],
+            ),
+            // Noisy predictions
+            (
+                &[
+                    Some(300),
+                    Some(320),
+                    Some(200),
+                    Some(290),
+                    Some(180),
+                    Some(150),
+                ],
+                &[
+                    Some(300),
+                    Some(295),
+                    Some(233),
+                    Some(233),
+                    Some(192),
+                    Some(156),
+                ],
+            ),
+            // Arriving, never counting below zero
+            (
+                &[Some(40), Some(10), Some(30), Some(0)],
+                &[Some(40), Some(10), Some(15), Some(0)],
+            ),
+        ],
+    );
+}
+
+#[test]
+fn restarts_on_reappearing() {
+    check(
+        config(60, 50),
+        &[(
+            &[Some(300), None, Some(400), Some(380)],
+            &[Some(300), None, Some(400), Some(375)],
+        )],
+    );
+}
+
+#[test]
+fn continues_through_a_ghost() {
+    // As the request task, the tracker retains the train missing from a response
+    let mut tracker = VehicleTracker::new();
+    let mut smoother = CountdownSmoother::new(config(60, 50));
+    let shown: std::vec::Vec<_> = [Some(300), None, Some(400)]
+        .iter()
+        .map(|countdown| {
+            let mut predictions = response(*countdown);
+            tracker.process(&mut predictions, CYCLE_SECS);
+            smoother.process(&mut predictions, CYCLE_SECS);
+            predictions
+                .first()
+                .map(|p| (p.time_to_station, p.uncertain))
+        })
+        .collect();
+    assert_eq!(
+        shown,
+        [Some((300, false)), Some((270, true)), Some((270, false))]
+    );
+}
+
+#[test]
+fn follows_the_clock_or_predictions_at_the_extremes() {
+    let predicted: &[Option<u32>] = &[Some(300), Some(200), Some(400), Some(170)];
+    check(
+        config(60, 0),
+        &[(predicted, &[Some(300), Some(270), Some(240), Some(210)])],
+    );
+    check(
+        config(60, 1),
+        &[(predicted, &[Some(300), Some(270), Some(240), Some(210)])],
+    );
+    check(
+        config(60, 100),
+        &[(predicted, &[Some(300), Some(200), Some(230), Some(170)])],
+    );
+    // Beyond 100, as 100
+    check(
+        config(60, 200),
+        &[(predicted, &[Some(300), Some(200), Some(230), Some(170)])],
+    );
+    check(
+        config(0, 100),
+        &[(predicted, &[Some(300), Some(200), Some(170), Some(140)])],
+    );
+    check(
+        config(1, 100),
+        &[(predicted, &[Some(300), Some(200), Some(171), Some(142)])],
+    );
+}
+
+#[test]
+fn passes_through_untracked_predictions() {
+    let mut smoother = CountdownSmoother::new(config(60, 50));
+    for countdown in [300, 400] {
+        let mut predictions: Vec<Prediction, ARRAY_MAX_SIZE_PREDICTION_MODEL> = [Prediction {
+            vehicle_id: BoundedString::new(),
+            ..prediction(0, "Upminster", "", countdown)
+        }]
+        .into_iter()
+        .collect();
+        smoother.process(&mut predictions, CYCLE_SECS);
+        assert_eq!(predictions[0].time_to_station, countdown);
+    }
+}
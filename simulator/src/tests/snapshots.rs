@@ -0,0 +1,15 @@
+//! Golden image snapshot tests
+//!
+//! Draws every screen of the snapshot matrix, and compares it against the
+//! references checked in to `snapshots/`, as `cargo run -- --check`.
+//!
+use crate::snapshots;
+
+#[test]
+fn screens_match_snapshots() {
+    // Any that differ are written alongside, as <name>.actual.png
+    assert!(
+        snapshots::check(&snapshots::directory()).unwrap(),
+        "screens differ from the snapshots, see `cargo run -- --check`"
+    );
+}
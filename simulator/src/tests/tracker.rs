@@ -0,0 +1,182 @@
+//! Duplicate and ghost prediction tests
+//!
+//! Replays request cycles through the tracker, as the request task would,
+//! with trains duplicated within a response, and missing from responses.
+//!
+use heapless::Vec;
+
+use crate::filters::tracker::VehicleTracker;
+use crate::fixtures::prediction;
+use crate::models::bounded::BoundedString;
+use crate::models::prediction::{ARRAY_MAX_SIZE_PREDICTION_MODEL, Prediction};
+
+// Time between the request cycles
+const CYCLE_SECS: u32 = 30;
+
+/// Process a response, as (vehicle id, destination, seconds to station), returning
+/// (vehicle id, destination, seconds to station, uncertain)
+fn cycle(
+    tracker: &mut VehicleTracker,
+    response: &[(usize, &str, u32)],
+) -> std::vec::Vec<(String, String, u32, bool)> {
+    let mut predictions: Vec<Prediction, ARRAY_MAX_SIZE_PREDICTION_MODEL> = response
+        .iter()
+        .map(|(vehicle_id, destination, time_to_station)| {
+            prediction(*vehicle_id, destination, "", *time_to_station)
+        })
+        .collect();
+    tracker.process(&mut predictions, CYCLE_SECS);
+
+    predictions
+        .iter()
+        .map(|p| {
+            (
+                p.vehicle_id.to_string(),
+                p.destination_name.to_string(),
+                p.time_to_station,
+                p.uncertain,
+            )
+        })
+        .collect()
+}
+
+/// An expected prediction, as (vehicle id, destination, seconds to station, uncertain)
+fn expected(
+    vehicle_id: &str,
+    destination: &str,
+    time_to_station: u32,
+    uncertain: bool,
+) -> (String, String, u32, bool) {
+    (
+        vehicle_id.to_string(),
+        destination.to_string(),
+        time_to_station,
+        uncertain,
+    )
+}
+
+#[test]
+fn keeps_the_soonest_of_duplicates() {
+    let mut tracker = VehicleTracker::new();
+    let predictions = cycle(
+        &mut tracker,
+        &[
+            (1, "Upminster", 240),
+            (2, "Barking", 360),
+            (1, "Upminster", 180),
+        ],
+    );
+    assert_eq!(
+        predictions,
+        [
+            expected("1", "Upminster", 180, false),
+            expected("2", "Barking", 360, false),
+        ]
+    );
+}
+
+#[test]
+fn keeps_a_vehicle_to_each_destination() {
+    // The same train, reported once terminating early and once to its destination
+    let mut tracker = VehicleTracker::new();
+    let predictions = cycle(
+        &mut tracker,
+        &[(1, "Upminster", 180), (1, "Tower Hill", 120)],
+    );
+    assert_eq!(
+        predictions,
+        [
+            expected("1", "Tower Hill", 120, false),
+            expected("1", "Upminster", 180, false),
+        ]
+    );
+}
+
+#[test]
+fn passes_through_untracked_predictions() {
+    let mut predictions: Vec<Prediction, ARRAY_MAX_SIZE_PREDICTION_MODEL> = [
+        prediction(0, "Upminster", "", 180),
+        prediction(0, "Upminster", "", 180),
+    ]
+    .into_iter()
+    .map(|p| Prediction {
+        vehicle_id: BoundedString::new(),
+        ..p
+    })
+    .collect();
+
+    let mut tracker = VehicleTracker::new();
+    tracker.process(&mut predictions, CYCLE_SECS);
+    assert_eq!(predictions.len(), 2);
+
+    // Nor are they retained when missing
+    let mut predictions = Vec::new();
+    tracker.process(&mut predictions, CYCLE_SECS);
+    assert!(predictions.is_empty());
+}
+
+#[test]
+fn retains_a_ghost_for_one_cycle() {
+    let mut tracker = VehicleTracker::new();
+    cycle(&mut tracker, &[(1, "Upminster", 180), (2, "Barking", 360)]);
+
+    // Missing once, retained as uncertain, counted down by the time elapsed
+    let predictions = cycle(&mut tracker, &[(1, "Upminster", 150)]);
+    assert_eq!(
+        predictions,
+        [
+            expected("1", "Upminster", 150, false),
+            expected("2", "Barking", 330, true),
+        ]
+    );
+
+    // Missing twice, dropped
+    let predictions = cycle(&mut tracker, &[(1, "Upminster", 120)]);
+    assert_eq!(predictions, [expected("1", "Upminster", 120, false)]);
+
+    // And not carried on from before
+    let predictions = cycle(&mut tracker, &[(1, "Upminster", 90)]);
+    assert_eq!(predictions, [expected("1", "Upminster", 90, false)]);
+}
+
+#[test]
+fn ghost_reappearing_is_certain() {
+    let mut tracker = VehicleTracker::new();
+    cycle(&mut tracker, &[(1, "Upminster", 180), (2, "Barking", 360)]);
+    cycle(&mut tracker, &[(1, "Upminster", 150)]);
+
+    let predictions = cycle(&mut tracker, &[(1, "Upminster", 120), (2, "Barking", 290)]);
+    assert_eq!(
+        predictions,
+        [
+            expected("1", "Upminster", 120, false),
+            expected("2", "Barking", 290, false),
+        ]
+    );
+}
+
+#[test]
+fn drops_a_ghost_that_should_have_arrived() {
+    let mut tracker = VehicleTracker::new();
+    cycle(
+        &mut tracker,
+        &[(1, "Upminster", CYCLE_SECS), (2, "Barking", 360)],
+    );
+
+    let predictions = cycle(&mut tracker, &[(2, "Barking", 330)]);
+    assert_eq!(predictions, [expected("2", "Barking", 330, false)]);
+}
+
+#[test]
+fn retains_the_only_vehicle_vanishing() {
+    let mut tracker = VehicleTracker::new();
+    cycle(&mut tracker, &[(1, "Upminster", 180)]);
+
+    // An empty response, retained as uncertain rather than no predictions
+    let predictions = cycle(&mut tracker, &[]);
+    assert_eq!(predictions, [expected("1", "Upminster", 150, true)]);
+
+    // Still missing, so none
+    let predictions = cycle(&mut tracker, &[]);
+    assert!(predictions.is_empty());
+}
@@ -0,0 +1,137 @@
+//! URL builder tests
+//!
+//! The exact URL of each endpoint the request task requests, and the
+//! percent-encoding, omission of empty query values and capacity of URLs.
+//!
+use crate::tasks::request::url::{
+    UrlBuilder, UrlError, arrivals_url, line_status_url, planned_status_url, update_url,
+};
+
+const TFL_API: &str = "https://api.tfl.gov.uk";
+const PROXY: &str = "http://192.168.1.10:8080/";
+
+// East Putney
+const STOPCODE: &str = "940GZZLUEPY";
+
+#[test]
+fn arrivals() {
+    assert_eq!(
+        arrivals_url(TFL_API, STOPCODE, "0123abcd").unwrap(),
+        "https://api.tfl.gov.uk/StopPoint/940GZZLUEPY/Arrivals?api_key=0123abcd"
+    );
+    assert_eq!(
+        arrivals_url(PROXY, STOPCODE, "").unwrap(),
+        "http://192.168.1.10:8080/StopPoint/940GZZLUEPY/Arrivals"
+    );
+}
+
+#[test]
+fn update() {
+    assert_eq!(
+        update_url(PROXY, "district", STOPCODE, "0123abcd").unwrap(),
+        "http://192.168.1.10:8080/Update/district/940GZZLUEPY?api_key=0123abcd"
+    );
+}
+
+#[test]
+fn line_status() {
+    assert_eq!(
+        line_status_url(TFL_API, "district", "0123abcd").unwrap(),
+        "https://api.tfl.gov.uk/Line/district/Status?api_key=0123abcd"
+    );
+}
+
+#[test]
+fn planned_closures() {
+    assert_eq!(
+        planned_status_url(
+            TFL_API,
+            "district",
+            ("2025-10-25", "2025-10-26"),
+            "0123abcd"
+        )
+        .unwrap(),
+        "https://api.tfl.gov.uk/Line/district/Status/2025-10-25/to/2025-10-26?api_key=0123abcd"
+    );
+}
+
+#[test]
+fn percent_encodes() {
+    let cases: &[(&str, &str)] = &[
+        ("Earl's Court", "Earl%27s%20Court"),
+        ("a&b=c?d", "a%26b%3Dc%3Fd"),
+        ("Châtelet", "Ch%C3%A2telet"),
+        ("/", "%2F"),
+        // Unreserved characters
+        ("AZaz09-._~", "AZaz09-._~"),
+    ];
+    for (value, encoded) in cases {
+        let url = UrlBuilder::<256>::new(TFL_API)
+            .segment(value)
+            .query(value, value)
+            .build()
+            .unwrap();
+        assert_eq!(
+            url.as_str(),
+            format!("{TFL_API}/{encoded}?{encoded}={encoded}")
+        );
+    }
+}
+
+#[test]
+fn omits_empty_query_values() {
+    let cases: &[(&[(&str, &str)], &str)] = &[
+        (&[("api_key", "")], ""),
+        (&[("app_id", ""), ("api_key", "k")], "?api_key=k"),
+        (&[("app_id", "a"), ("api_key", "")], "?app_id=a"),
+        (
+            &[("app_id", "a"), ("mode", ""), ("api_key", "k")],
+            "?app_id=a&api_key=k",
+        ),
+    ];
+    for (queries, expected) in cases {
+        let url = queries
+            .iter()
+            .fold(UrlBuilder::<256>::new(TFL_API), |builder, (key, value)| {
+                builder.query(key, value)
+            })
+            .build()
+            .unwrap();
+        assert_eq!(url.as_str(), format!("{TFL_API}{expected}"), "{queries:?}");
+    }
+}
+
+#[test]
+fn exceeding_the_capacity_is_an_error() {
+    // Exactly the capacity
+    assert_eq!(
+        UrlBuilder::<22>::new(TFL_API).build().unwrap(),
+        "https://api.tfl.gov.uk"
+    );
+    assert_eq!(
+        UrlBuilder::<21>::new(TFL_API).build(),
+        Err(UrlError::CapacityExceeded)
+    );
+    assert_eq!(
+        UrlBuilder::<26>::new(TFL_API).segment("Line").build(),
+        Err(UrlError::CapacityExceeded)
+    );
+    // Within a percent-encoded character
+    assert_eq!(
+        UrlBuilder::<25>::new(TFL_API).segment("a ").build(),
+        Err(UrlError::CapacityExceeded)
+    );
+    // And not cleared by anything that fits after
+    assert_eq!(
+        UrlBuilder::<24>::new(TFL_API)
+            .segment("Line")
+            .query("a", "")
+            .segment("")
+            .build(),
+        Err(UrlError::CapacityExceeded)
+    );
+    assert_eq!(
+        arrivals_url(TFL_API, STOPCODE, &"k".repeat(256)),
+        Err(UrlError::CapacityExceeded)
+    );
+}
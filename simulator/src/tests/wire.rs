@@ -0,0 +1,140 @@
+//! Proxy wire format tests
+//!
+//! Encodes updates as the proxy would, then decodes them as the firmware
+//! does, including payloads from a proxy of another schema version, and
+//! payloads cut short in transit.
+//!
+use heapless::Vec;
+
+use crate::fixtures::{self, prediction};
+use crate::models::bounded::BoundedString;
+use crate::models::status::LineStatus;
+use crate::models::update::Fault;
+use crate::models::wire::{WIRE_SCHEMA_VERSION, WireError, WireUpdate};
+
+// Large enough for any update
+const BUFFER_SIZE: usize = 2048;
+
+/// An update from the proxy, of the given schema version
+fn wire_update(schema_version: u16) -> WireUpdate {
+    WireUpdate {
+        schema_version,
+        generated_at: 1_761_390_000,
+        line_name: BoundedString::truncating("District"),
+        platform_name: BoundedString::truncating("Eastbound - Platform 1"),
+        station_name: BoundedString::truncating("East Putney"),
+        line_statuses: [LineStatus {
+            status_severity_description: BoundedString::truncating("Minor Delays"),
+        }]
+        .into_iter()
+        .collect(),
+        arrivals: [
+            prediction(1, "Upminster", "At Putney Bridge", 60),
+            prediction(2, "Barking", "Left Parsons Green", 180),
+        ]
+        .into_iter()
+        .collect(),
+        messages: [BoundedString::truncating("Proxy message")]
+            .into_iter()
+            .collect(),
+    }
+}
+
+#[test]
+fn round_trips_an_update() {
+    let update = wire_update(WIRE_SCHEMA_VERSION);
+    let mut buffer = [0u8; BUFFER_SIZE];
+    let payload = update.encode(&mut buffer).unwrap();
+
+    let decoded = WireUpdate::decode(payload).unwrap();
+    assert_eq!(decoded.schema_version, WIRE_SCHEMA_VERSION);
+    assert_eq!(decoded.generated_at, update.generated_at);
+    assert_eq!(decoded.age_secs(update.generated_at + 30), 30);
+    assert_eq!(decoded.line_name, update.line_name);
+    assert_eq!(decoded.platform_name, update.platform_name);
+    assert_eq!(decoded.station_name, update.station_name);
+    assert_eq!(
+        decoded.line_statuses[0].status_severity_description,
+        update.line_statuses[0].status_severity_description
+    );
+    assert_eq!(decoded.arrivals.len(), update.arrivals.len());
+    for (decoded, arrival) in decoded.arrivals.iter().zip(update.arrivals.iter()) {
+        assert_eq!(decoded.vehicle_id, arrival.vehicle_id);
+        assert_eq!(decoded.destination_name, arrival.destination_name);
+        assert_eq!(decoded.current_location, arrival.current_location);
+        assert_eq!(decoded.time_to_station, arrival.time_to_station);
+        assert!(!decoded.uncertain);
+    }
+    assert_eq!(decoded.messages, update.messages);
+}
+
+#[test]
+fn schema_mismatch_is_a_fault() {
+    let found = WIRE_SCHEMA_VERSION + 1;
+    let mut buffer = [0u8; BUFFER_SIZE];
+    let payload = wire_update(found).encode(&mut buffer).unwrap();
+
+    let error = WireUpdate::decode(payload).unwrap_err();
+    assert_eq!(
+        error,
+        WireError::VersionMismatch {
+            expected: WIRE_SCHEMA_VERSION,
+            found,
+        }
+    );
+    assert_eq!(
+        error.fault(),
+        Some(Fault::SchemaMismatch {
+            expected: WIRE_SCHEMA_VERSION,
+            found,
+        })
+    );
+}
+
+#[test]
+fn truncated_payload_is_malformed() {
+    let mut buffer = [0u8; BUFFER_SIZE];
+    let payload = wire_update(WIRE_SCHEMA_VERSION)
+        .encode(&mut buffer)
+        .unwrap();
+
+    // Cut short anywhere, including within the schema version
+    for len in 0..payload.len() {
+        assert_eq!(
+            WireUpdate::decode(&payload[..len]).unwrap_err(),
+            WireError::Malformed,
+            "cut to {len} of {} bytes",
+            payload.len()
+        );
+    }
+    assert_eq!(WireError::Malformed.fault(), None);
+}
+
+#[test]
+fn empty_update_round_trips() {
+    let update = WireUpdate {
+        line_statuses: Vec::new(),
+        arrivals: Vec::new(),
+        messages: Vec::new(),
+        ..wire_update(WIRE_SCHEMA_VERSION)
+    };
+    let mut buffer = [0u8; BUFFER_SIZE];
+    let payload = update.encode(&mut buffer).unwrap();
+
+    let decoded = WireUpdate::decode(payload).unwrap();
+    assert!(decoded.line_statuses.is_empty());
+    assert!(decoded.arrivals.is_empty());
+    assert!(decoded.messages.is_empty());
+}
+
+#[test]
+fn applies_to_the_display_update() {
+    let mut update = fixtures::fixture("fault").unwrap();
+    wire_update(WIRE_SCHEMA_VERSION).apply(&mut update);
+
+    assert_eq!(update.fault, None);
+    assert_eq!(update.line_status.as_str(), "Minor Delays");
+    assert_eq!(update.arrivals.len(), 2);
+    assert_eq!(update.generated_at, Some(1_761_390_000));
+    assert_eq!(update.messages, wire_update(WIRE_SCHEMA_VERSION).messages);
+}
@@ -0,0 +1,3 @@
+pub mod names;
+pub mod smoothing;
+pub mod tracker;
@@ -0,0 +1,131 @@
+//! Station name normalisation
+//!
+//! TFL names such as "East Putney Underground Station" or "Paddington (H&C
+//! Line)-Underground" waste much of the display. This post-processing stage
+//! normalises the station, destination and current location names of each
+//! prediction, by:
+//!
+//! 1. Applying any user override from the `NamesConfig` for the raw name
+//! 2. Stripping suffixes such as "Underground Station", "Rail Station" and
+//!    line qualifiers such as "(H&C Line)"
+//! 3. Applying any user override, then the compiled in abbreviation, for the
+//!    stripped name, e.g. "Kensington (Olympia)" to "Olympia"
+//!
+use heapless::{String, Vec};
+
+use crate::config::NamesConfig;
+use crate::models::bounded::BoundedString;
+use crate::models::prediction::{ARRAY_MAX_SIZE_PREDICTION_MODEL, Prediction};
+
+// Removed wherever they appear in a name
+const SUFFIXES: &[&str] = &[
+    "-Underground",
+    " Underground Station",
+    " Rail Station",
+    " DLR Station",
+    " Overground Station",
+];
+
+// Removed only from the end of a name
+const TRAILING_SUFFIXES: &[&str] = &[" Station"];
+
+// Compiled in abbreviations, for the stripped name, and of names too wide to display, see `fit`
+pub const ABBREVIATIONS: &[(&str, &str)] = &[
+    ("Heathrow Terminals 2 & 3", "Heathrow T2&3"),
+    ("Heathrow Terminals 1-2-3", "Heathrow T1-2-3"),
+    ("Heathrow Terminal 4", "Heathrow T4"),
+    ("Heathrow Terminal 5", "Heathrow T5"),
+    ("Kensington (Olympia)", "Olympia"),
+    ("King's Cross St. Pancras", "King's Cross"),
+    ("High Street Kensington", "High St Kensington"),
+    ("Shepherd's Bush Market", "Shepherd's Bush Mkt"),
+    ("Cutty Sark (for Maritime Greenwich)", "Cutty Sark"),
+];
+
+// Abbreviations of the words of names still too wide to display, as used on TFL signage
+pub const WORD_ABBREVIATIONS: &[(&str, &str)] = &[
+    ("Street", "St"),
+    ("Road", "Rd"),
+    ("Park", "Pk"),
+    ("Square", "Sq"),
+    ("Green", "Grn"),
+    ("Junction", "Jn"),
+    ("Central", "Ctl"),
+    ("Broadway", "Bdwy"),
+    ("Terminals", "T"),
+    ("Terminal", "T"),
+    ("North", "N"),
+    ("South", "S"),
+    ("East", "E"),
+    ("West", "W"),
+    ("and", "&"),
+];
+
+pub struct NameNormaliser {
+    config: NamesConfig,
+}
+
+impl NameNormaliser {
+    pub const fn new(config: NamesConfig) -> Self {
+        Self { config }
+    }
+
+    /// Normalise the names of each of the predictions
+    pub fn process(&self, predictions: &mut Vec<Prediction, ARRAY_MAX_SIZE_PREDICTION_MODEL>) {
+        for prediction in predictions.iter_mut() {
+            prediction.station_name = self.normalise(&prediction.station_name);
+            prediction.destination_name = self.normalise(&prediction.destination_name);
+            prediction.current_location = self.normalise(&prediction.current_location);
+        }
+    }
+
+    /// Normalise a single name
+    pub fn normalise<const N: usize>(&self, name: &BoundedString<N>) -> BoundedString<N> {
+        if let Some(replacement) = lookup(self.config.overrides, name) {
+            return BoundedString::truncating(replacement);
+        }
+
+        let stripped = strip::<N>(name);
+        let replacement = lookup(self.config.overrides, &stripped)
+            .or_else(|| lookup(ABBREVIATIONS, &stripped))
+            .unwrap_or(&stripped);
+        BoundedString::truncating(replacement)
+    }
+}
+
+fn lookup<'a>(table: &'a [(&str, &str)], name: &str) -> Option<&'a str> {
+    table
+        .iter()
+        .find(|(from, _)| *from == name)
+        .map(|(_, to)| *to)
+}
+
+/// Strip the suffixes and any trailing line qualifier, e.g. "(Circle Line)"
+fn strip<const N: usize>(name: &str) -> String<N> {
+    let mut stripped = String::<N>::new();
+    let mut rest = name;
+    while let Some((idx, suffix)) = SUFFIXES
+        .iter()
+        .filter_map(|suffix| rest.find(suffix).map(|idx| (idx, suffix)))
+        .min_by_key(|(idx, _)| *idx)
+    {
+        let _ = stripped.push_str(&rest[..idx]);
+        rest = &rest[idx + suffix.len()..];
+    }
+    let _ = stripped.push_str(rest);
+
+    let mut end = stripped.trim_end().len();
+    for suffix in TRAILING_SUFFIXES {
+        if stripped[..end].ends_with(suffix) {
+            end -= suffix.len();
+        }
+    }
+    if stripped[..end].ends_with("Line)")
+        && let Some(idx) = stripped[..end].rfind(" (")
+    {
+        end = idx;
+    }
+    stripped.truncate(stripped[..end].trim_end().len());
+
+    stripped
+}
@@ -0,0 +1,81 @@
+//! Countdown smoothing
+//!
+//! `time_to_station` from the TFL API is noisy, and can bounce 3 -> 5 -> 2
+//! minutes between requests. This post-processing stage smooths the countdown
+//! of each vehicle against the local clock: the previous countdown is advanced
+//! by the time elapsed, any increase beyond that is clamped, and the result is
+//! then blended towards the latest prediction with an exponential filter.
+//!
+//! The aggressiveness of both is set by the `SmoothingConfig`.
+//!
+//! Note: Predictions without a vehicle id cannot be tracked, so are passed
+//! through untouched.
+//!
+use heapless::Vec;
+
+use crate::config::SmoothingConfig;
+use crate::models::TFL_API_FIELD_STR_SIZE;
+use crate::models::bounded::BoundedString;
+use crate::models::prediction::{ARRAY_MAX_SIZE_PREDICTION_MODEL, Prediction};
+
+struct Countdown {
+    vehicle_id: BoundedString<TFL_API_FIELD_STR_SIZE>,
+    time_to_station: u32,
+}
+
+pub struct CountdownSmoother {
+    config: SmoothingConfig,
+    // Smoothed countdowns from the previous cycle
+    previous: Vec<Countdown, ARRAY_MAX_SIZE_PREDICTION_MODEL>,
+}
+
+impl CountdownSmoother {
+    pub const fn new(config: SmoothingConfig) -> Self {
+        Self {
+            config,
+            previous: Vec::new(),
+        }
+    }
+
+    /// Smooth the countdowns of the predictions against those of the previous cycle.
+    /// `elapsed_secs` is the time since the previous cycle was processed.
+    pub fn process(
+        &mut self,
+        predictions: &mut Vec<Prediction, ARRAY_MAX_SIZE_PREDICTION_MODEL>,
+        elapsed_secs: u32,
+    ) {
+        for prediction in predictions.iter_mut() {
+            let previous = self
+                .previous
+                .iter()
+                .find(|c| !c.vehicle_id.is_empty() && c.vehicle_id == prediction.vehicle_id);
+
+            if let Some(previous) = previous {
+                prediction.time_to_station = self.smooth(
+                    previous.time_to_station.saturating_sub(elapsed_secs),
+                    prediction.time_to_station,
+                );
+            }
+        }
+
+        // Sort array by which is arriving first
+        predictions.sort_unstable_by_key(|p| p.time_to_station);
+
+        self.previous.clear();
+        for prediction in predictions.iter() {
+            // Cannot overflow, as both are bound by the same maximum
+            let _ = self.previous.push(Countdown {
+                vehicle_id: prediction.vehicle_id.clone(),
+                time_to_station: prediction.time_to_station,
+            });
+        }
+    }
+
+    /// Blend the `expected` countdown (per the local clock) towards the `predicted` countdown
+    fn smooth(&self, expected: u32, predicted: u32) -> u32 {
+        let clamped = predicted.min(expected.saturating_add(self.config.max_increase_secs));
+        let responsiveness = self.config.responsiveness_percent.min(100) as i64;
+        let delta = (clamped as i64 - expected as i64) * responsiveness / 100;
+        (expected as i64 + delta) as u32
+    }
+}
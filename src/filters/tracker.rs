@@ -0,0 +1,87 @@
+//! Duplicate and ghost prediction suppression
+//!
+//! The TFL API frequently returns the same train twice (different prediction
+//! ids, same vehicle), and trains can "vanish" from one response only to
+//! reappear in the next. Both make the board flicker between two and three
+//! entries.
+//!
+//! This post-processing stage runs after filtering for the platform of
+//! interest. It deduplicates predictions by vehicle id and destination, and
+//! tracks vehicles across request cycles, so that a train briefly missing from
+//! one response is retained (marked as uncertain) for a single cycle.
+//!
+//! Note: Predictions without a vehicle id cannot be tracked, so are passed
+//! through untouched.
+//!
+use heapless::Vec;
+
+use crate::models::prediction::{ARRAY_MAX_SIZE_PREDICTION_MODEL, Prediction};
+
+pub struct VehicleTracker {
+    // Predictions seen in the previous cycle
+    previous: Vec<Prediction, ARRAY_MAX_SIZE_PREDICTION_MODEL>,
+}
+
+impl VehicleTracker {
+    pub const fn new() -> Self {
+        Self {
+            previous: Vec::new(),
+        }
+    }
+
+    /// Deduplicate the predictions and retain any ghosts from the previous cycle.
+    /// `elapsed_secs` is the time since the previous cycle was processed.
+    pub fn process(
+        &mut self,
+        predictions: &mut Vec<Prediction, ARRAY_MAX_SIZE_PREDICTION_MODEL>,
+        elapsed_secs: u32,
+    ) {
+        // Drop duplicates, keeping the soonest prediction for each vehicle
+        let mut unique: Vec<Prediction, ARRAY_MAX_SIZE_PREDICTION_MODEL> = Vec::new();
+        for prediction in predictions.drain(..) {
+            match unique.iter_mut().find(|p| is_same_vehicle(p, &prediction)) {
+                Some(existing) => {
+                    if prediction.time_to_station < existing.time_to_station {
+                        *existing = prediction;
+                    }
+                }
+                None => {
+                    // Cannot overflow, as there are never more unique than original predictions
+                    let _ = unique.push(prediction);
+                }
+            }
+        }
+
+        // Retain vehicles that went missing this cycle, for this cycle only.
+        // Ghosts are not carried again, and those that should have arrived are dropped.
+        for ghost in self.previous.iter() {
+            if ghost.uncertain
+                || ghost.vehicle_id.is_empty()
+                || ghost.time_to_station <= elapsed_secs
+                || unique.iter().any(|p| is_same_vehicle(p, ghost))
+            {
+                continue;
+            }
+
+            let mut ghost = ghost.clone();
+            ghost.time_to_station -= elapsed_secs;
+            ghost.uncertain = true;
+            if unique.push(ghost).is_err() {
+                break;
+            }
+        }
+
+        // Sort array by which is arriving first
+        unique.sort_unstable_by_key(|p| p.time_to_station);
+
+        self.previous = unique.clone();
+        *predictions = unique;
+    }
+}
+
+/// Whether two predictions are for the same (identifiable) vehicle and destination
+fn is_same_vehicle(a: &Prediction, b: &Prediction) -> bool {
+    !a.vehicle_id.is_empty()
+        && a.vehicle_id == b.vehicle_id
+        && a.destination_name == b.destination_name
+}
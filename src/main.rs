@@ -4,6 +4,7 @@
 
 use ::function_name::named;
 use assign_resources::assign_resources;
+use core::cell::RefCell;
 use cyw43::JoinOptions;
 use cyw43::aligned_bytes;
 use cyw43_pio::{PioSpi, RM2_CLOCK_DIVIDER};
@@ -13,37 +14,46 @@ use embassy_executor::Spawner;
 use embassy_net::{Config, StackResources};
 use embassy_rp::bind_interrupts;
 use embassy_rp::clocks::RoscRng;
+use embassy_rp::flash::{Blocking, Flash};
 use embassy_rp::gpio::{Input, Level, Output};
 use embassy_rp::peripherals::{DMA_CH0, DMA_CH1, PIO0};
 use embassy_rp::pio::{InterruptHandler, Pio};
 use embassy_rp::spi;
 use embassy_rp::spi::Spi;
+use embassy_rp::usb;
 use embassy_rp::{Peri, peripherals};
 use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
 use embassy_sync::mutex::Mutex;
 use embassy_sync::signal::Signal;
 use embassy_time::Delay;
-use embassy_time::{Duration, Timer};
+use embassy_time::{Duration, Instant, Timer};
 use embedded_hal_bus::spi::ExclusiveDevice;
-use epd_waveshare::epd3in7::EPD3in7;
-use epd_waveshare::prelude::WaveshareDisplay;
-use heapless::{String, Vec};
+use heapless::Vec;
 use static_cell::StaticCell;
 
 mod config;
+mod filters;
 mod models;
 mod panic;
 mod schedule;
+mod secrets;
+mod system;
 mod tasks;
 
-use config::{ScheduleConfig, WifiConfig};
+use config::{DisplayConfig, ScheduleConfig};
 
+use crate::models::bounded::BoundedString;
+use crate::models::system::SystemState;
 use crate::models::update::Update;
 use crate::models::{
     TFL_API_FIELD_LONG_STR_SIZE, TFL_API_FIELD_SHORT_STR_SIZE, TFL_API_FIELD_STR_SIZE,
 };
 use crate::schedule::Schedule;
+use crate::secrets::{FLASH_SIZE, SecretsStore};
+use crate::tasks::button::button_task;
+use crate::tasks::console::console_task;
 use crate::tasks::display::display_task;
+use crate::tasks::display::pins::SharedPin;
 use crate::tasks::ntp::ntp_task;
 use crate::tasks::request::request_task;
 
@@ -68,6 +78,7 @@ const CHIP_SPECIFIC_CLOCK_DIVIDER: FixedU32<U8> = RM2_CLOCK_DIVIDER;
 
 bind_interrupts!(struct Irqs {
     PIO0_IRQ_0 => InterruptHandler<PIO0>;
+    USBCTRL_IRQ => usb::InterruptHandler<peripherals::USB>;
     DMA_IRQ_0 => embassy_rp::dma::InterruptHandler<DMA_CH0>, embassy_rp::dma::InterruptHandler<DMA_CH1>;});
 
 #[embassy_executor::task]
@@ -105,6 +116,13 @@ assign_resources! {
         pin_25: PIN_25,
         pin_29: PIN_29,
     }
+    console_resources: ConsoleResources {
+        usb: USB,
+        flash: FLASH,
+    }
+    button_resources: ButtonResources {
+        pin_15: PIN_15,
+    }
 }
 
 // Static for communication between tasks
@@ -112,23 +130,30 @@ assign_resources! {
 // - No copies - static lifetime and mutex ensures data is always shared without copying
 // - No channel update locks - channels act as queues, only the latest is ever needed,
 // even with a channel size of 0, data must be read before it can be replaced
-// - Slow display updates - full display updates take in the order of 3 seconds, there is no
-// scenario where a pubsub works as partial updates are not currently possible and data from
-// the API is unlikely to change in the interval of 30 seconds
+// - Slow display updates - full display updates take in the order of 3 seconds, and even
+// partial updates redraw from the latest data, so there is no scenario where a pubsub works
+// and data from the API is unlikely to change in the interval of 30 seconds
 // - Request tasks waits - the request task should not wait for the display to finish reading
 // as the display should always display the latest data from the request task (no stale updates)
 static UPDATE: Mutex<CriticalSectionRawMutex, Update> = Mutex::new(Update {
     arrivals: Vec::new(),
-    line_name: String::<TFL_API_FIELD_STR_SIZE>::new(),
-    line_status: String::<TFL_API_FIELD_SHORT_STR_SIZE>::new(),
-    platform_name: String::<TFL_API_FIELD_STR_SIZE>::new(),
-    station_name: String::<TFL_API_FIELD_LONG_STR_SIZE>::new(),
+    line_name: BoundedString::<TFL_API_FIELD_STR_SIZE>::new(),
+    line_status: BoundedString::<TFL_API_FIELD_SHORT_STR_SIZE>::new(),
+    platform_name: BoundedString::<TFL_API_FIELD_STR_SIZE>::new(),
+    station_name: BoundedString::<TFL_API_FIELD_LONG_STR_SIZE>::new(),
+    fault: None,
+    closures: Vec::new(),
+    generated_at: None,
+    messages: Vec::new(),
 });
 
 // Atomic signal for the request task to emit, and the display task to consume
 // to know when there is new data to physically show.
 static NOTIFY: Signal<CriticalSectionRawMutex, ()> = Signal::new();
 
+// How long to wait for a DHCP lease, before showing that there is no IP address
+const DHCP_TIMEOUT: Duration = Duration::from_secs(30);
+
 // active/inactive schedule
 pub const SCHEDULE: Schedule = Schedule::from_config(ScheduleConfig::new());
 
@@ -148,13 +173,19 @@ async fn main(spawner: Spawner) {
     // Setup display pins and SPI bus
     let pin_reset: Output<'_> = Output::new(split_p.display_resources.pin_12, Level::Low);
     let pin_cs = Output::new(split_p.display_resources.pin_9, Level::High);
-    let pin_data_cmd: Output<'_> = Output::new(split_p.display_resources.pin_8, Level::Low);
+    // BUSY and DC are shared with the grayscale renderer
+    static DISPLAY_DATA_CMD: StaticCell<RefCell<Output<'static>>> = StaticCell::new();
+    static DISPLAY_BUSY: StaticCell<RefCell<Input<'static>>> = StaticCell::new();
+    let pin_data_cmd = SharedPin::new(DISPLAY_DATA_CMD.init(RefCell::new(Output::new(
+        split_p.display_resources.pin_8,
+        Level::Low,
+    ))));
     let pin_spi_sclk = split_p.display_resources.pin_10;
     let pin_spi_mosi = split_p.display_resources.pin_11;
-    let pin_busy = Input::new(
+    let pin_busy = SharedPin::new(DISPLAY_BUSY.init(RefCell::new(Input::new(
         split_p.display_resources.pin_13,
         embassy_rp::gpio::Pull::None,
-    );
+    ))));
     let mut display_config = spi::Config::default();
     const DISPLAY_FREQ: u32 = 4_000_000;
     display_config.frequency = DISPLAY_FREQ;
@@ -166,28 +197,39 @@ async fn main(spawner: Spawner) {
         pin_spi_mosi,
         display_config,
     );
-    let mut spi_device: ExclusiveDevice<
+    let spi_device: ExclusiveDevice<
         Spi<'_, embassy_rp::peripherals::SPI1, spi::Blocking>,
         Output<'_>,
         Delay,
     > = ExclusiveDevice::new(spi_bus, pin_cs, Delay).expect("Display: SPI initalise error");
-    // Setup the EPD driver
-    let epd_driver = EPD3in7::new(
-        &mut spi_device,
+
+    // Spawn display task, which initialises the panel
+    spawner.spawn(unwrap!(display_task(
+        spi_device,
         pin_busy,
         pin_data_cmd,
-        pin_reset,
-        &mut Delay,
-        None,
-    )
-    .expect("Display: eink initalise error"); // Force unwrap, as there is nothing that can be done if this errors out
+        pin_reset
+    )));
 
-    // Spawn display task
-    spawner.spawn(unwrap!(display_task(epd_driver, spi_device)));
+    // Spawn the button task, to toggle the large type theme, unless there is no button
+    if DisplayConfig::new().large_type_button {
+        let button = Input::new(split_p.button_resources.pin_15, embassy_rp::gpio::Pull::Up);
+        spawner.spawn(unwrap!(button_task(button)));
+    }
 
     // Allow display task to run and show splash before continuing setup
     Timer::after_millis(500).await;
 
+    // Load the provisioned secrets, falling back to those compiled in
+    info!("{}: Loading secrets...", function_name!());
+    let flash = Flash::<_, Blocking, FLASH_SIZE>::new_blocking(split_p.console_resources.flash);
+    let mut secrets_store = SecretsStore::new(flash);
+    secrets::init(&mut secrets_store);
+
+    // Spawn the console task, to allow secrets to be provisioned over USB
+    let usb_driver = usb::Driver::new(split_p.console_resources.usb, Irqs);
+    spawner.spawn(unwrap!(console_task(usb_driver, secrets_store)));
+
     // Setup the CYW43 Wifi chip
     info!("{}: Initialising CYW43 Wifi chip...", function_name!());
     let pwr = Output::new(split_p.network_resources.pin_23, Level::Low);
@@ -231,27 +273,42 @@ async fn main(spawner: Spawner) {
     );
 
     spawner.spawn(unwrap!(net_task(runner)));
-    let wifi_config = WifiConfig::new();
+    let wifi_secrets = secrets::current();
+    system::set(SystemState::JoiningWifi { status: None });
     loop {
         match control
             .join(
-                wifi_config.ssid,
-                JoinOptions::new(wifi_config.password.as_bytes()),
+                wifi_secrets.wifi_ssid.as_str(),
+                JoinOptions::new(wifi_secrets.wifi_password.as_bytes()),
             )
             .await
         {
             Ok(_) => break,
             Err(err) => {
                 info!("{}: join failed with status={}", function_name!(), err);
+                system::set(SystemState::JoiningWifi {
+                    status: Some(err.status),
+                });
             }
         }
     }
+    system::set(SystemState::Starting);
+    system::update_boot_progress(|progress| progress.wifi = true);
 
     // Wait for DHCP, not necessary when using static IP
     info!("{}: waiting for DHCP...", function_name!());
+    let dhcp_started = Instant::now();
     while !stack.is_config_up() {
+        if dhcp_started.elapsed() > DHCP_TIMEOUT {
+            system::set(SystemState::NoIp);
+        }
         Timer::after_millis(100).await;
     }
+    system::set(SystemState::Starting);
+    if let Some(config) = stack.config_v4() {
+        let address = config.address.address().octets();
+        system::update_boot_progress(|progress| progress.address = Some(address));
+    }
     info!("{}: DHCP is now up!", function_name!());
 
     info!("{}: waiting for link up...", function_name!());
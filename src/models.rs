@@ -1,7 +1,11 @@
-pub const TFL_API_FIELD_SHORT_STR_SIZE: usize = 16;
-pub const TFL_API_FIELD_STR_SIZE: usize = 32;
-pub const TFL_API_FIELD_LONG_STR_SIZE: usize = 72;
+mod sizes;
 
+pub use sizes::*;
+
+pub mod boot;
+pub mod bounded;
 pub mod prediction;
 pub mod status;
+pub mod system;
 pub mod update;
+pub mod wire;
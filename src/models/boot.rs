@@ -0,0 +1,30 @@
+//! Boot progress model
+//!
+//! The steps of the boot sequence completed so far, shown by the display as a
+//! checklist, so that it is clear where setup stalls. The steps after joining
+//! the Wi-Fi network run concurrently, so may complete in any order.
+//!
+use defmt::Format;
+
+#[derive(Copy, Clone, Debug, Format, Eq, PartialEq)]
+pub struct BootProgress {
+    /// Joined the Wi-Fi network
+    pub wifi: bool,
+    /// IP address leased by DHCP
+    pub address: Option<[u8; 4]>,
+    /// Wall clock synced by NTP
+    pub time: bool,
+    /// First predictions fetched from the TfL API
+    pub tfl: bool,
+}
+
+impl BootProgress {
+    pub const fn new() -> Self {
+        Self {
+            wifi: false,
+            address: None,
+            time: false,
+            tfl: false,
+        }
+    }
+}
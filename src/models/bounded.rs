@@ -0,0 +1,115 @@
+//! Truncating bounded string
+//!
+//! A fixed capacity string for the TFL API models that, rather than failing
+//! deserialisation when a value is longer than its capacity, truncates the
+//! value at a UTF-8 character boundary and marks it with an ellipsis.
+//!
+//! With `heapless::String`, a single over-length field (e.g. a new status
+//! wording, or an unusually long station name) fails deserialisation of the
+//! *whole* response, leaving the board blank. With this, a long value instead
+//! degrades to something like "Heathrow Terminals 2 & 3 Undergr...".
+//!
+use core::fmt;
+use core::ops::Deref;
+
+use defmt::Format;
+use heapless::String;
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::{Serialize, Serializer};
+
+/// Appended to truncated values, where the capacity allows
+const ELLIPSIS: &str = "...";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BoundedString<const N: usize> {
+    value: String<N>,
+}
+
+impl<const N: usize> BoundedString<N> {
+    pub const fn new() -> Self {
+        Self {
+            value: String::new(),
+        }
+    }
+
+    /// Create from a string slice, truncating it if it exceeds the capacity
+    pub fn truncating(s: &str) -> Self {
+        let mut value = String::new();
+        if value.push_str(s).is_ok() {
+            return Self { value };
+        }
+
+        // Leave room for the ellipsis, cutting back to the nearest character boundary
+        let keep = floor_char_boundary(s, N.saturating_sub(ELLIPSIS.len()));
+        let kept = s[..keep].trim_end();
+        let _ = value.push_str(kept);
+        let _ = value.push_str(&ELLIPSIS[..(N - kept.len()).min(ELLIPSIS.len())]);
+
+        Self { value }
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.value.as_str()
+    }
+}
+
+/// The largest index no greater than `index` that lies on a character boundary of `s`
+fn floor_char_boundary(s: &str, index: usize) -> usize {
+    let mut index = index.min(s.len());
+    while !s.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+impl<const N: usize> Deref for BoundedString<N> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> From<&str> for BoundedString<N> {
+    fn from(s: &str) -> Self {
+        Self::truncating(s)
+    }
+}
+
+impl<const N: usize> fmt::Display for BoundedString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl<const N: usize> Format for BoundedString<N> {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{=str}", self.as_str())
+    }
+}
+
+impl<const N: usize> Serialize for BoundedString<N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for BoundedString<N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BoundedStringVisitor<const N: usize>;
+
+        impl<'de, const N: usize> Visitor<'de> for BoundedStringVisitor<N> {
+            type Value = BoundedString<N>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a string")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                Ok(BoundedString::truncating(v))
+            }
+        }
+
+        deserializer.deserialize_str(BoundedStringVisitor::<N>)
+    }
+}
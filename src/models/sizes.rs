@@ -0,0 +1,10 @@
+//! Sizes of the TFL API string fields
+//!
+//! Kept out of `models.rs`, as the simulator includes each model it needs
+//! individually, rather than the whole module.
+//!
+pub const TFL_API_FIELD_SHORT_STR_SIZE: usize = 16;
+pub const TFL_API_FIELD_STR_SIZE: usize = 32;
+pub const TFL_API_FIELD_LONG_STR_SIZE: usize = 72;
+// Free text, such as the reason for a disruption
+pub const TFL_API_FIELD_TEXT_STR_SIZE: usize = 160;
@@ -3,6 +3,7 @@
 //! https://api-portal.tfl.gov.uk/api-details#api=Line&operation=Line_StatusByIdsByPathIdsQueryDetail
 //! https://api-portal.tfl.gov.uk/api-details#api=Line&operation=Line_StatusByIdsByPathIdsQueryDetail&definition=Tfl-16
 //! https://api.tfl.gov.uk/Line/{ids}/Status
+//! https://api.tfl.gov.uk/Line/{ids}/Status/{startDate}/to/{endDate}
 //!
 //!
 //! Note: A number of fields are commented out, this is because they are useful
@@ -11,19 +12,20 @@
 //! necessary for conveying information are retained.
 //!
 use defmt::Format;
-use heapless::{String, Vec};
-use serde::Deserialize;
+use heapless::Vec;
+use serde::{Deserialize, Serialize};
 
-use crate::models::TFL_API_FIELD_SHORT_STR_SIZE;
+use crate::models::bounded::BoundedString;
+use crate::models::{TFL_API_FIELD_SHORT_STR_SIZE, TFL_API_FIELD_TEXT_STR_SIZE};
 
 pub const ARRAY_MAX_SIZE_LINE_STATUS_MODEL: usize = 1;
 
-#[derive(Deserialize, Debug, Format)]
+#[derive(Deserialize, Serialize, Debug, Format)]
 #[serde(rename_all = "camelCase")]
 pub struct LineStatus {
     // #[serde(rename = "$type")]
     // pub _type: String<TFL_API_FIELD_LONG_STR_SIZE>,
-    pub status_severity_description: String<TFL_API_FIELD_SHORT_STR_SIZE>,
+    pub status_severity_description: BoundedString<TFL_API_FIELD_SHORT_STR_SIZE>,
     // Incomplete implementation, as much of the data is not required
 }
 
@@ -37,3 +39,21 @@ pub struct Status {
     pub line_statuses: Vec<LineStatus, ARRAY_MAX_SIZE_STATUS_MODEL>,
     // Incomplete implementation, as much of the data is not required
 }
+
+/// A line status over a date range, with the reason for it, e.g. a planned closure
+#[derive(Deserialize, Debug, Format)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedLineStatus {
+    pub status_severity_description: BoundedString<TFL_API_FIELD_SHORT_STR_SIZE>,
+    // Absent for a good service
+    #[serde(default)]
+    pub reason: BoundedString<TFL_API_FIELD_TEXT_STR_SIZE>,
+    // Incomplete implementation, as much of the data is not required
+}
+
+#[derive(Deserialize, Debug, Format)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedStatus {
+    pub line_statuses: Vec<PlannedLineStatus, ARRAY_MAX_SIZE_STATUS_MODEL>,
+    // Incomplete implementation, as much of the data is not required
+}
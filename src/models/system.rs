@@ -0,0 +1,79 @@
+//! System state model
+//!
+//! The connectivity of the board, and the outcome of its latest requests, each
+//! shown by the display as a screen of its own in place of the arrivals, see
+//! `crate::system`.
+//!
+use core::fmt::Write as _;
+use defmt::Format;
+use heapless::String;
+
+// Longest troubleshooting link, of the page and the state
+pub const TROUBLESHOOTING_LINK_SIZE: usize = 128;
+
+/// State of the board, from joining the Wi-Fi network to fetching predictions
+#[derive(Copy, Clone, Debug, Format, Eq, PartialEq)]
+pub enum SystemState {
+    /// Starting up, with nothing yet known to have failed
+    Starting,
+    /// Joining the Wi-Fi network, with the status of the last failed attempt
+    JoiningWifi { status: Option<u32> },
+    /// Joined the Wi-Fi network, but no IP address has been leased by DHCP
+    NoIp,
+    /// The API host name could not be resolved
+    DnsFailed,
+    /// The secure connection to the API host could not be established
+    TlsFailed,
+    /// The API key was rejected, with the HTTP status
+    AuthFailed { status: u16 },
+    /// Requests to the API keep failing, with the HTTP status of the last, if it responded
+    RequestFailed { code: Option<u16> },
+    /// The API returned no predictions for the platform
+    NoPredictions,
+    /// Predictions are being fetched, and shown
+    Ready,
+}
+
+impl SystemState {
+    /// Whether starting up, with nothing yet known to have failed
+    pub fn is_starting(&self) -> bool {
+        matches!(self, Self::Starting | Self::JoiningWifi { status: None })
+    }
+
+    /// The last error code, where the cause reports one
+    pub fn code(&self) -> Option<u32> {
+        match self {
+            Self::JoiningWifi { status } => *status,
+            Self::AuthFailed { status } => Some(*status as u32),
+            Self::RequestFailed { code } => code.map(u32::from),
+            _ => None,
+        }
+    }
+
+    /// Short name, as used by the troubleshooting notes
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Starting => "starting",
+            Self::JoiningWifi { .. } => "joining-wifi",
+            Self::NoIp => "no-ip",
+            Self::DnsFailed => "dns-failed",
+            Self::TlsFailed => "tls-failed",
+            Self::AuthFailed { .. } => "auth-failed",
+            Self::RequestFailed { .. } => "request-failed",
+            Self::NoPredictions => "no-predictions",
+            Self::Ready => "ready",
+        }
+    }
+
+    /// Link to the troubleshooting notes for the state on the page, with any error code,
+    /// e.g. `<page>?code=401#auth-failed`, or `None` if too long
+    pub fn troubleshooting_link(&self, page: &str) -> Option<String<TROUBLESHOOTING_LINK_SIZE>> {
+        let mut link = String::new();
+        write!(&mut link, "{}", page).ok()?;
+        if let Some(code) = self.code() {
+            write!(&mut link, "?code={}", code).ok()?;
+        }
+        write!(&mut link, "#{}", self.name()).ok()?;
+        Some(link)
+    }
+}
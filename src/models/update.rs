@@ -3,18 +3,36 @@
 //! This collates the data from various requests and configurations.
 //!
 use defmt::Format;
-use heapless::{String, Vec};
+use heapless::Vec;
 
+use crate::models::bounded::BoundedString;
 use crate::models::prediction::{ARRAY_MAX_SIZE_PREDICTION_MODEL, Prediction};
 use crate::models::{
     TFL_API_FIELD_LONG_STR_SIZE, TFL_API_FIELD_SHORT_STR_SIZE, TFL_API_FIELD_STR_SIZE,
 };
 
+pub const ARRAY_MAX_SIZE_CLOSURES: usize = 2;
+pub const ARRAY_MAX_SIZE_MESSAGES: usize = 2;
+
 #[derive(Debug, Format, Clone)]
 pub struct Update {
     pub arrivals: Vec<Prediction, ARRAY_MAX_SIZE_PREDICTION_MODEL>,
-    pub line_name: String<TFL_API_FIELD_STR_SIZE>,
-    pub line_status: String<TFL_API_FIELD_SHORT_STR_SIZE>,
-    pub platform_name: String<TFL_API_FIELD_STR_SIZE>,
-    pub station_name: String<TFL_API_FIELD_LONG_STR_SIZE>,
+    pub line_name: BoundedString<TFL_API_FIELD_STR_SIZE>,
+    pub line_status: BoundedString<TFL_API_FIELD_SHORT_STR_SIZE>,
+    pub platform_name: BoundedString<TFL_API_FIELD_STR_SIZE>,
+    pub station_name: BoundedString<TFL_API_FIELD_LONG_STR_SIZE>,
+    pub fault: Option<Fault>,
+    // Planned closures of the line this weekend, shown while the board sleeps
+    pub closures: Vec<BoundedString<TFL_API_FIELD_LONG_STR_SIZE>, ARRAY_MAX_SIZE_CLOSURES>,
+    // Unix time (seconds) at which the proxy generated the update, shown as last updated
+    pub generated_at: Option<u64>,
+    // Messages from the proxy, the first shown in the footer
+    pub messages: Vec<BoundedString<TFL_API_FIELD_LONG_STR_SIZE>, ARRAY_MAX_SIZE_MESSAGES>,
+}
+
+/// Conditions where the update data cannot be trusted, shown in place of arrivals
+#[derive(Copy, Clone, Debug, Format, Eq, PartialEq)]
+pub enum Fault {
+    /// The proxy encodes a different wire schema version to this firmware
+    SchemaMismatch { expected: u16, found: u16 },
 }
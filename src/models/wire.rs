@@ -0,0 +1,108 @@
+//! The Proxy Wire Model
+//!
+//! A compact, versioned binary schema for a display update, produced by a
+//! proxy sitting in front of the TFL API and encoded with postcard. Decoding
+//! this is far cheaper in RAM than parsing the raw API JSON responses, and it
+//! is decoded straight into the existing models.
+//!
+//! Note: postcard encodes fields in declaration order, with no field names.
+//! The schema version must therefore remain the first field, and any change to
+//! the layout of `WireUpdate` (or the models it contains) must increment
+//! `WIRE_SCHEMA_VERSION`, so that a mismatched proxy is detected rather than
+//! silently decoded into garbage.
+//!
+use defmt::Format;
+use heapless::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::models::bounded::BoundedString;
+use crate::models::prediction::{ARRAY_MAX_SIZE_PREDICTION_MODEL, Prediction};
+use crate::models::status::{ARRAY_MAX_SIZE_STATUS_MODEL, LineStatus};
+use crate::models::update::{ARRAY_MAX_SIZE_MESSAGES, Fault, Update};
+use crate::models::{TFL_API_FIELD_LONG_STR_SIZE, TFL_API_FIELD_STR_SIZE};
+
+pub const WIRE_SCHEMA_VERSION: u16 = 1;
+
+#[derive(Deserialize, Serialize, Debug, Format)]
+pub struct WireUpdate {
+    pub schema_version: u16,
+    // Unix time (seconds) at which the proxy generated the update
+    pub generated_at: u64,
+    pub line_name: BoundedString<TFL_API_FIELD_STR_SIZE>,
+    pub platform_name: BoundedString<TFL_API_FIELD_STR_SIZE>,
+    pub station_name: BoundedString<TFL_API_FIELD_LONG_STR_SIZE>,
+    pub line_statuses: Vec<LineStatus, ARRAY_MAX_SIZE_STATUS_MODEL>,
+    pub arrivals: Vec<Prediction, ARRAY_MAX_SIZE_PREDICTION_MODEL>,
+    pub messages: Vec<BoundedString<TFL_API_FIELD_LONG_STR_SIZE>, ARRAY_MAX_SIZE_MESSAGES>,
+}
+
+/// Represents any error that may happen when decoding or encoding a wire update.
+#[derive(Copy, Clone, Debug, Format, Eq, PartialEq)]
+pub enum WireError {
+    /// The payload was encoded with a different schema version to this firmware
+    VersionMismatch { expected: u16, found: u16 },
+    /// The payload could not be decoded or encoded
+    Malformed,
+}
+
+impl WireError {
+    /// The fault to show in place of the arrivals, if the payload can never be decoded by this
+    /// firmware, rather than being a one off
+    pub fn fault(self) -> Option<Fault> {
+        match self {
+            WireError::VersionMismatch { expected, found } => {
+                Some(Fault::SchemaMismatch { expected, found })
+            }
+            WireError::Malformed => None,
+        }
+    }
+}
+
+impl WireUpdate {
+    /// Decode a postcard payload, checking the schema version before the body
+    pub fn decode(bytes: &[u8]) -> Result<Self, WireError> {
+        let (found, _) =
+            postcard::take_from_bytes::<u16>(bytes).map_err(|_| WireError::Malformed)?;
+        if found != WIRE_SCHEMA_VERSION {
+            return Err(WireError::VersionMismatch {
+                expected: WIRE_SCHEMA_VERSION,
+                found,
+            });
+        }
+
+        postcard::from_bytes::<Self>(bytes).map_err(|_| WireError::Malformed)
+    }
+
+    /// Encode into a postcard payload, returning the used portion of the buffer
+    ///
+    /// Only the proxy encodes updates, so this is for the host tests.
+    #[cfg(test)]
+    pub fn encode<'a>(&self, buffer: &'a mut [u8]) -> Result<&'a mut [u8], WireError> {
+        postcard::to_slice(self, buffer).map_err(|_| WireError::Malformed)
+    }
+
+    /// Seconds since the proxy generated the update, as of the unix time `now`
+    pub fn age_secs(&self, now: u64) -> u64 {
+        now.saturating_sub(self.generated_at)
+    }
+
+    /// Collate the wire update into the display update
+    pub fn apply(self, update: &mut Update) {
+        update.fault = None;
+        update.generated_at = Some(self.generated_at);
+        update.messages = self.messages;
+
+        if !self.arrivals.is_empty() {
+            update.line_name = self.line_name;
+            update.platform_name = self.platform_name;
+            update.station_name = self.station_name;
+            update.arrivals = self.arrivals;
+        }
+
+        update.line_status = match self.line_statuses.first() {
+            Some(line_status) => line_status.status_severity_description.clone(),
+            // No status data returned = everything is running perfectly fine!
+            None => BoundedString::truncating("Good Service"),
+        };
+    }
+}
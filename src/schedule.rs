@@ -26,6 +26,15 @@ impl Schedule {
         }
     }
 
+    /// The time (hour, minute, second) the active window begins
+    pub const fn active_at(&self) -> (u32, u32, u32) {
+        (
+            self.active_seconds / 3600,
+            (self.active_seconds % 3600) / 60,
+            self.active_seconds % 60,
+        )
+    }
+
     /// Convert a hour, min, sec tuple into seconds
     /// This allows for simple comparisons of times within the same day
     const fn time_to_seconds(t: (u32, u32, u32)) -> u32 {
@@ -0,0 +1,188 @@
+//! Runtime provisioned secrets
+//!
+//! The Wi-Fi credentials and TFL API key are loaded at boot from a dedicated
+//! flash sector, so that they can be changed without a rebuild and reflash,
+//! and need not be compiled into the ELF at all. The constants in `config.rs`
+//! are only used as fallback defaults, when nothing has been provisioned.
+//!
+//! Secrets are provisioned at runtime over the USB serial console, see
+//! `tasks::console`.
+//!
+//! The secrets sector is the last sector of the flash, which is excluded from
+//! the `FLASH` region of the linker script, so is never overwritten when
+//! flashing new firmware. A record in the sector is a magic number, a checksum
+//! of the payload, and the postcard encoded `Secrets` payload.
+//!
+use core::cell::RefCell;
+use defmt::{Format, info, warn};
+use embassy_sync::blocking_mutex::Mutex;
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embedded_storage::nor_flash::{NorFlash, ReadNorFlash};
+use heapless::String;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{TflApiRequestConfig, WifiConfig};
+
+pub const SECRET_SSID_SIZE: usize = 32;
+pub const SECRET_PASSWORD_SIZE: usize = 64;
+pub const SECRET_API_KEY_SIZE: usize = 64;
+
+// Size of the on-board flash, must match the linker script (`rp2350.x`)
+pub const FLASH_SIZE: usize = 16 * 1024 * 1024;
+
+// Secrets are stored in the last erasable sector of the flash
+const SECRETS_SECTOR_SIZE: usize = 4096;
+const SECRETS_SECTOR_OFFSET: u32 = (FLASH_SIZE - SECRETS_SECTOR_SIZE) as u32;
+
+// Record layout: magic (4 bytes), checksum (4 bytes), payload
+const RECORD_MAGIC: u32 = 0x4C50_5453; // "LPTS"
+const RECORD_HEADER_SIZE: usize = 8;
+const RECORD_SIZE: usize = 256;
+
+// Static secrets, as loaded at boot or updated via the console
+// Access is thread & core safe via a blocking CriticalSectionRawMutex, as for the wall clock
+pub static SECRETS: Mutex<CriticalSectionRawMutex, RefCell<Secrets>> =
+    Mutex::new(RefCell::new(Secrets::new()));
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Secrets {
+    pub wifi_ssid: String<SECRET_SSID_SIZE>,
+    pub wifi_password: String<SECRET_PASSWORD_SIZE>,
+    pub api_primary_key: String<SECRET_API_KEY_SIZE>,
+}
+
+impl Secrets {
+    const fn new() -> Self {
+        Self {
+            wifi_ssid: String::new(),
+            wifi_password: String::new(),
+            api_primary_key: String::new(),
+        }
+    }
+
+    /// The compiled in secrets, used when nothing has been provisioned
+    pub fn fallback() -> Self {
+        let wifi_config = WifiConfig::new();
+        let tfl_api_request_config = TflApiRequestConfig::new();
+        Self {
+            wifi_ssid: String::try_from(wifi_config.ssid).unwrap_or_default(),
+            wifi_password: String::try_from(wifi_config.password).unwrap_or_default(),
+            api_primary_key: String::try_from(tfl_api_request_config.api_primary_key)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+// Never log the secret values themselves
+impl Format for Secrets {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "Secrets {{ wifi_ssid: {=str}, wifi_password: <{=usize} chars>, api_primary_key: <{=usize} chars> }}",
+            self.wifi_ssid.as_str(),
+            self.wifi_password.len(),
+            self.api_primary_key.len()
+        )
+    }
+}
+
+/// Get a copy of the current secrets
+pub fn current() -> Secrets {
+    SECRETS.lock(|cell| cell.borrow().clone())
+}
+
+/// Replace the current secrets
+pub fn set(secrets: Secrets) {
+    SECRETS.lock(|cell| {
+        *cell.borrow_mut() = secrets;
+    });
+}
+
+/// Represents any error that may happen when storing secrets.
+#[derive(Copy, Clone, Debug, Format, Eq, PartialEq)]
+pub enum SecretsError {
+    /// The secrets could not be encoded into a record
+    EncodingFailed,
+    /// The flash could not be erased or written
+    FlashFailed,
+}
+
+/// Persistent storage of the secrets in the dedicated flash sector
+pub struct SecretsStore<F> {
+    flash: F,
+}
+
+impl<F: NorFlash + ReadNorFlash> SecretsStore<F> {
+    pub fn new(flash: F) -> Self {
+        Self { flash }
+    }
+
+    /// Read the provisioned secrets, if there are any and they are intact
+    pub fn load(&mut self) -> Option<Secrets> {
+        let mut record = [0u8; RECORD_SIZE];
+        self.flash.read(SECRETS_SECTOR_OFFSET, &mut record).ok()?;
+
+        let (header, payload) = record.split_at(RECORD_HEADER_SIZE);
+        let (magic, checksum) = header.split_at(4);
+        if u32::from_le_bytes(magic.try_into().ok()?) != RECORD_MAGIC {
+            return None;
+        }
+
+        let (secrets, unused) = postcard::take_from_bytes::<Secrets>(payload).ok()?;
+        let used = payload.len() - unused.len();
+        if u32::from_le_bytes(checksum.try_into().ok()?) != fnv1a(&payload[..used]) {
+            return None;
+        }
+
+        Some(secrets)
+    }
+
+    /// Persist the secrets, replacing any previously provisioned
+    pub fn save(&mut self, secrets: &Secrets) -> Result<(), SecretsError> {
+        // Unused bytes are left as erased flash
+        let mut record = [0xFFu8; RECORD_SIZE];
+        let used = postcard::to_slice(secrets, &mut record[RECORD_HEADER_SIZE..])
+            .map_err(|_| SecretsError::EncodingFailed)?
+            .len();
+        let checksum = fnv1a(&record[RECORD_HEADER_SIZE..RECORD_HEADER_SIZE + used]);
+        record[..4].copy_from_slice(&RECORD_MAGIC.to_le_bytes());
+        record[4..RECORD_HEADER_SIZE].copy_from_slice(&checksum.to_le_bytes());
+
+        self.erase()?;
+        self.flash
+            .write(SECRETS_SECTOR_OFFSET, &record)
+            .map_err(|_| SecretsError::FlashFailed)
+    }
+
+    /// Erase any provisioned secrets, reverting to the compiled in fallback
+    pub fn erase(&mut self) -> Result<(), SecretsError> {
+        self.flash
+            .erase(
+                SECRETS_SECTOR_OFFSET,
+                SECRETS_SECTOR_OFFSET + SECRETS_SECTOR_SIZE as u32,
+            )
+            .map_err(|_| SecretsError::FlashFailed)
+    }
+}
+
+/// Load the secrets at boot, falling back to the compiled in secrets
+pub fn init<F: NorFlash + ReadNorFlash>(store: &mut SecretsStore<F>) {
+    let secrets = match store.load() {
+        Some(secrets) => {
+            info!("Loaded provisioned secrets: {}", secrets);
+            secrets
+        }
+        None => {
+            warn!("No provisioned secrets found, falling back to compiled in secrets");
+            Secrets::fallback()
+        }
+    };
+    set(secrets);
+}
+
+/// FNV-1a hash, to detect torn or corrupt records
+fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811C_9DC5u32, |hash, byte| {
+        (hash ^ *byte as u32).wrapping_mul(0x0100_0193)
+    })
+}
@@ -0,0 +1,85 @@
+//! System state
+//!
+//! Set by the main task while connecting, and by the request task from the
+//! outcome of its requests, so that the display can show why there are no
+//! arrivals, rather than leaving the splash or stale data up.
+//!
+//! The progress through the boot sequence is also recorded here, for the
+//! display to show as a checklist while starting.
+//!
+use core::cell::Cell;
+use defmt::info;
+use embassy_sync::blocking_mutex::Mutex;
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+
+use crate::NOTIFY;
+use crate::models::boot::BootProgress;
+use crate::models::system::SystemState;
+
+// Static system state
+// Access is thread & core safe via a blocking CriticalSectionRawMutex, as for the wall clock
+static SYSTEM_STATE: Mutex<CriticalSectionRawMutex, Cell<SystemState>> =
+    Mutex::new(Cell::new(SystemState::Starting));
+
+// Consecutive failed requests, as for the system state
+static FAILED_REQUESTS: Mutex<CriticalSectionRawMutex, Cell<u32>> = Mutex::new(Cell::new(0));
+
+// Consecutive failed requests before the failure is shown, so that a single dropped request, or
+// a single slow cycle, leaves the arrivals up
+const FAILED_REQUESTS_SHOWN: u32 = 3;
+
+// Static boot progress, as for the system state
+static BOOT_PROGRESS: Mutex<CriticalSectionRawMutex, Cell<BootProgress>> =
+    Mutex::new(Cell::new(BootProgress::new()));
+
+/// The current system state
+pub fn current() -> SystemState {
+    SYSTEM_STATE.lock(|cell| cell.get())
+}
+
+/// Set the system state, signalling the display task if it has changed
+pub fn set(state: SystemState) {
+    let previous = SYSTEM_STATE.lock(|cell| cell.replace(state));
+    if previous != state {
+        info!("System state changed from {} to {}", previous, state);
+        NOTIFY.signal(());
+    }
+}
+
+/// Record a failed request, with the HTTP status if the API responded, showing the failure once
+/// enough have failed in a row
+pub fn request_failed(code: Option<u16>) {
+    let failed = FAILED_REQUESTS.lock(|cell| {
+        let failed = cell.get().saturating_add(1);
+        cell.set(failed);
+        failed
+    });
+    if failed >= FAILED_REQUESTS_SHOWN {
+        set(SystemState::RequestFailed { code });
+    }
+}
+
+/// Record fetched arrivals, restarting the count of failed requests
+pub fn request_succeeded() {
+    FAILED_REQUESTS.lock(|cell| cell.set(0));
+}
+
+/// The progress through the boot sequence
+pub fn boot_progress() -> BootProgress {
+    BOOT_PROGRESS.lock(|cell| cell.get())
+}
+
+/// Record progress through the boot sequence, signalling the display task if it has changed
+pub fn update_boot_progress(update: impl FnOnce(&mut BootProgress)) {
+    let (previous, progress) = BOOT_PROGRESS.lock(|cell| {
+        let previous = cell.get();
+        let mut progress = previous;
+        update(&mut progress);
+        cell.set(progress);
+        (previous, progress)
+    });
+    if previous != progress {
+        info!("Boot progress: {}", progress);
+        NOTIFY.signal(());
+    }
+}
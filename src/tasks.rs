@@ -1,3 +1,5 @@
+pub mod button;
+pub mod console;
 pub mod display;
 pub mod ntp;
 pub mod request;
@@ -0,0 +1,58 @@
+//! Button task
+//!
+//! This task toggles the large type theme, showing only the next train, see
+//! `display::large_type`, from a push button wired between GPIO 15 and
+//! ground. Each press switches between the configured theme and large type,
+//! and the display is signalled to redraw the latest update straight away.
+//!
+//! Enabled by `DISPLAY_LARGE_TYPE_BUTTON`.
+//!
+
+use ::function_name::named;
+use core::sync::atomic::{AtomicBool, Ordering};
+use defmt::info;
+use embassy_rp::gpio::Input;
+use embassy_time::Timer;
+
+use crate::NOTIFY;
+use crate::tasks::display::draw::Theme;
+
+// Time for the contacts to settle, after which the button must still be pressed
+const DEBOUNCE_MS: u64 = 50;
+
+// Whether the button has switched from the configured theme
+static TOGGLED: AtomicBool = AtomicBool::new(false);
+
+/// The theme to draw the update in, the configured theme unless toggled by the button
+pub fn theme(configured: Theme) -> Theme {
+    match (TOGGLED.load(Ordering::Relaxed), configured) {
+        (false, theme) => theme,
+        (true, Theme::LargeType) => Theme::Classic,
+        (true, _) => Theme::LargeType,
+    }
+}
+
+#[named]
+#[embassy_executor::task(pool_size = 1)]
+pub async fn button_task(mut button: Input<'static>) {
+    loop {
+        // Pulled up, so pressed when low
+        button.wait_for_falling_edge().await;
+        Timer::after_millis(DEBOUNCE_MS).await;
+        if button.is_high() {
+            continue;
+        }
+
+        let toggled = !TOGGLED.fetch_xor(true, Ordering::Relaxed);
+        info!(
+            "{}: Button pressed, large type toggled {}",
+            function_name!(),
+            toggled
+        );
+        NOTIFY.signal(());
+
+        // Once per press, however long held
+        button.wait_for_high().await;
+        Timer::after_millis(DEBOUNCE_MS).await;
+    }
+}
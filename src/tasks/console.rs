@@ -0,0 +1,227 @@
+//! USB serial console task
+//!
+//! This task exposes a line based console over USB (CDC-ACM), used to
+//! provision the secrets at runtime, without a rebuild and reflash.
+//!
+//! Connect with any serial terminal (e.g. `picocom /dev/ttyACM0`) and enter:
+//!
+//! ```text
+//! ssid <Wi-Fi network name>
+//! password <Wi-Fi password>
+//! api_key <TFL API primary key>
+//! save
+//! reboot
+//! ```
+//!
+//! Values are taken verbatim after the first space, as Wi-Fi passwords may
+//! contain spaces, and those of the secrets are echoed as `*`.
+//!
+//! A saved API key is used from the next request, whereas Wi-Fi credentials
+//! are only used when joining the network, so require a reboot.
+//!
+use ::function_name::named;
+use core::fmt::Write as _;
+use defmt::{error, info};
+use embassy_futures::join::join;
+use embassy_rp::flash::{Blocking, Flash};
+use embassy_rp::peripherals::{FLASH, USB};
+use embassy_rp::usb::Driver;
+use embassy_usb::class::cdc_acm::{CdcAcmClass, State};
+use embassy_usb::driver::EndpointError;
+use embassy_usb::{Builder, Config};
+use heapless::String;
+use static_cell::StaticCell;
+
+use crate::secrets::{self, FLASH_SIZE, Secrets, SecretsStore};
+
+pub type ConsoleFlash = Flash<'static, FLASH, Blocking, FLASH_SIZE>;
+
+type ConsoleClass = CdcAcmClass<'static, Driver<'static, USB>>;
+
+// Maximum USB full speed packet size
+const MAX_PACKET_SIZE: usize = 64;
+
+// Maximum length of a command line, sufficient for the longest secret
+const MAX_LINE_LEN: usize = 96;
+
+// Commands whose values are secret, so are not echoed
+const SECRET_COMMANDS: &[&str] = &["password", "api_key"];
+
+#[named]
+#[embassy_executor::task(pool_size = 1)]
+pub async fn console_task(
+    driver: Driver<'static, USB>,
+    mut secrets_store: SecretsStore<ConsoleFlash>,
+) {
+    let mut config = Config::new(0xc0de, 0xcafe);
+    config.manufacturer = Some("its3mile");
+    config.product = Some("london-pi-tube console");
+    config.max_power = 100;
+    config.max_packet_size_0 = MAX_PACKET_SIZE as u8;
+
+    // Buffers must outlive the USB device, so are static
+    static CONFIG_DESCRIPTOR: StaticCell<[u8; 256]> = StaticCell::new();
+    static BOS_DESCRIPTOR: StaticCell<[u8; 256]> = StaticCell::new();
+    static CONTROL_BUF: StaticCell<[u8; 64]> = StaticCell::new();
+    static STATE: StaticCell<State> = StaticCell::new();
+
+    let mut builder = Builder::new(
+        driver,
+        config,
+        CONFIG_DESCRIPTOR.init([0; 256]),
+        BOS_DESCRIPTOR.init([0; 256]),
+        &mut [],
+        CONTROL_BUF.init([0; 64]),
+    );
+    let mut class = CdcAcmClass::new(
+        &mut builder,
+        STATE.init(State::new()),
+        MAX_PACKET_SIZE as u16,
+    );
+    let mut usb = builder.build();
+
+    let console = async {
+        loop {
+            class.wait_connection().await;
+            info!("{}: Console connected", function_name!());
+            let _ = run_console(&mut class, &mut secrets_store).await;
+            info!("{}: Console disconnected", function_name!());
+        }
+    };
+
+    join(usb.run(), console).await;
+}
+
+/// Read and handle command lines until the console disconnects
+async fn run_console(
+    class: &mut ConsoleClass,
+    secrets_store: &mut SecretsStore<ConsoleFlash>,
+) -> Result<(), EndpointError> {
+    // Edits are made to a pending copy, until saved
+    let mut pending = secrets::current();
+    let mut line = String::<MAX_LINE_LEN>::new();
+    let mut packet = [0u8; MAX_PACKET_SIZE];
+
+    write_line(class, "london-pi-tube console - enter 'help' for commands").await?;
+
+    loop {
+        let n = class.read_packet(&mut packet).await?;
+        for &byte in &packet[..n] {
+            match byte {
+                b'\r' | b'\n' => {
+                    if line.is_empty() {
+                        continue;
+                    }
+                    write_line(class, "").await?;
+                    let mut response = String::<128>::new();
+                    handle_command(
+                        line.trim_start(),
+                        &mut pending,
+                        secrets_store,
+                        &mut response,
+                    );
+                    write_line(class, &response).await?;
+                    line.clear();
+                }
+                // Backspace or delete
+                0x08 | 0x7F => {
+                    if line.pop().is_some() {
+                        class.write_packet(b"\x08 \x08").await?;
+                    }
+                }
+                byte if byte.is_ascii() && !byte.is_ascii_control() => {
+                    // Echo, so the user can see what is typed, masking the secrets
+                    let echo = if is_secret_value(&line) { b'*' } else { byte };
+                    if line.push(byte as char).is_ok() {
+                        class.write_packet(&[echo]).await?;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Handle a single command line, writing the response
+#[named]
+fn handle_command(
+    line: &str,
+    pending: &mut Secrets,
+    secrets_store: &mut SecretsStore<ConsoleFlash>,
+    response: &mut String<128>,
+) {
+    // Only the command is trimmed, the value may have leading or trailing spaces
+    let (command, value) = line.split_once(' ').unwrap_or((line.trim_end(), ""));
+
+    let _ = match command {
+        "show" => write!(
+            response,
+            "ssid: {}, password: <{} chars>, api_key: <{} chars>",
+            pending.wifi_ssid,
+            pending.wifi_password.len(),
+            pending.api_primary_key.len()
+        ),
+        "ssid" => set_value(&mut pending.wifi_ssid, value, response),
+        "password" => set_value(&mut pending.wifi_password, value, response),
+        "api_key" => set_value(&mut pending.api_primary_key, value, response),
+        "save" => match secrets_store.save(pending) {
+            Ok(_) => {
+                info!("{}: Saved provisioned secrets", function_name!());
+                secrets::set(pending.clone());
+                write!(response, "Saved - Wi-Fi changes take effect after 'reboot'")
+            }
+            Err(e) => {
+                error!("{}: Failed to save secrets: {}", function_name!(), e);
+                write!(response, "Failed to save: {:?}", e)
+            }
+        },
+        "reset" => match secrets_store.erase() {
+            Ok(_) => {
+                info!("{}: Erased provisioned secrets", function_name!());
+                *pending = Secrets::fallback();
+                secrets::set(pending.clone());
+                write!(response, "Erased - reverted to compiled in defaults")
+            }
+            Err(e) => {
+                error!("{}: Failed to erase secrets: {}", function_name!(), e);
+                write!(response, "Failed to erase: {:?}", e)
+            }
+        },
+        "reboot" => cortex_m::peripheral::SCB::sys_reset(),
+        _ => write!(
+            response,
+            "Commands: show, ssid <v>, password <v>, api_key <v>, save, reset, reboot"
+        ),
+    };
+}
+
+/// Whether what follows the partial line is the value of a secret
+fn is_secret_value(line: &str) -> bool {
+    match line.trim_start().split_once(' ') {
+        Some((command, _)) => SECRET_COMMANDS.contains(&command),
+        None => false,
+    }
+}
+
+/// Set a pending secret value, if it fits
+fn set_value<const N: usize>(
+    secret: &mut String<N>,
+    value: &str,
+    response: &mut String<128>,
+) -> core::fmt::Result {
+    match String::try_from(value) {
+        Ok(value) => {
+            *secret = value;
+            write!(response, "OK - enter 'save' to persist")
+        }
+        Err(_) => write!(response, "Too long - maximum of {} characters", N),
+    }
+}
+
+/// Write a line, split into packets
+async fn write_line(class: &mut ConsoleClass, line: &str) -> Result<(), EndpointError> {
+    for chunk in line.as_bytes().chunks(MAX_PACKET_SIZE) {
+        class.write_packet(chunk).await?;
+    }
+    class.write_packet(b"\r\n").await
+}
@@ -16,47 +16,85 @@
 //! | Good Service                                 Updated: 15:43 |
 //! +-------------------------------------------------------------+
 //!
+//! The update may instead be laid out as a dot-matrix departure board, as on
+//! the platforms, selected by `DISPLAY_THEME`, see `dot_matrix`.
+//!
+//! Or only the next train may be shown, in large type, by `DISPLAY_THEME` or
+//! toggled by a button, see `large_type` and `button`.
+//!
+//! Every screen may also be inverted, white on black, by
+//! `DISPLAY_COLOR_SCHEME`.
+//!
+//! Updates that only change the arrivals are shown with a partial refresh,
+//! and updates that change nothing drawn are not refreshed at all, see
+//! `refresh`. Full refreshes may optionally be rendered in grayscale, see
+//! `gray4`.
+//!
+//! Until predictions are fetched, a screen for the system state is shown in
+//! their place, e.g. when the Wi-Fi network cannot be joined, see `system`.
+//! While starting, this is a checklist of the boot progress.
+//!
+//! Through the inactive hours of the schedule, the panel sleeps showing when
+//! the board resumes, the line status and any planned weekend closures. The
+//! controller is also put into deep sleep between refreshes, unless disabled
+//! by `DISPLAY_SLEEP_BETWEEN_REFRESHES`, see `panel`.
+//!
+//! The screens are drawn by `draw`, and only rendered to the display here,
+//! through the panel selected by cargo feature, see `panel`.
+//!
 
 use ::function_name::named;
 use core::fmt::Write as _;
-use defmt::{error, info};
+use defmt::{error, info, warn};
 use embassy_rp::gpio::{Input, Output};
 use embassy_rp::spi;
 use embassy_rp::spi::Spi;
 use embassy_time::Delay;
-use embedded_graphics::image::Image;
-use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::pixelcolor::Gray2;
 use embedded_graphics::prelude::*;
-use embedded_iconoir::prelude::*;
 use epd_waveshare::color::Color;
-use epd_waveshare::epd3in7::Display3in7;
-use epd_waveshare::prelude::WaveshareDisplay;
 use heapless::String;
-use log::warn;
-use u8g2_fonts::{
-    FontRenderer, fonts,
-    types::{FontColor, HorizontalAlignment, VerticalPosition},
-};
 
 use embedded_hal_bus::spi::ExclusiveDevice;
-use epd_waveshare::{epd3in7::*, prelude::*};
 
+use crate::config::{DisplayConfig, StringsConfig, TroubleshootingConfig};
+use crate::models::boot::BootProgress;
+use crate::models::system::SystemState;
 use crate::models::update::Update;
-use crate::tasks::ntp::WALL_CLOCK;
+use crate::system;
+use crate::tasks::button;
+use crate::tasks::ntp::{WALL_CLOCK, format_london_time};
 use crate::{NOTIFY, SCHEDULE, UPDATE};
 
-/// The main display task that handles displaying sensor data and connection status
-pub type DisplayDriver = EPD3in7<
-    ExclusiveDevice<
-        Spi<'static, embassy_rp::peripherals::SPI1, spi::Blocking>,
-        Output<'static>,
-        Delay,
-    >,
-    Input<'static>,
-    Output<'static>,
-    Output<'static>,
-    Delay,
->;
+pub mod dot_matrix;
+pub mod draw;
+pub mod fit;
+pub mod gray4;
+pub mod large_type;
+pub mod layout;
+pub mod panel;
+pub mod pins;
+pub mod qr;
+pub mod refresh;
+pub mod strings;
+pub mod typeface;
+
+use draw::{
+    DisplayError, DisplayStyles, Sleep, draw_boot, draw_sleep, draw_splash, draw_state, draw_update,
+};
+use gray4::{Gray4Display, Gray4Epd};
+use layout::Scale;
+use panel::{DisplayBackend, GRAYSCALE_SUPPORTED, Panel, PanelError, QUICK_REFRESH_SUPPORTED};
+use pins::{BusyTimeout, SharedPin};
+use refresh::{Refresh, RefreshPlanner};
+use strings::Strings;
+
+/// The panel, with BUSY and DC shared with the grayscale renderer
+pub type DisplayPanel =
+    Panel<DisplaySpiDevice, SharedPin<Input<'static>>, SharedPin<Output<'static>>, Output<'static>>;
+
+pub type DisplayGrayEpd =
+    Gray4Epd<BusyTimeout<SharedPin<Input<'static>>>, SharedPin<Output<'static>>>;
 
 pub type DisplaySpiDevice = ExclusiveDevice<
     Spi<'static, embassy_rp::peripherals::SPI1, spi::Blocking>,
@@ -66,45 +104,166 @@ pub type DisplaySpiDevice = ExclusiveDevice<
 
 #[named]
 #[embassy_executor::task(pool_size = 1)]
-pub async fn display_task(mut epd_driver: DisplayDriver, mut spi_device: DisplaySpiDevice) {
-    // Create a Display buffer to draw on, specific for this ePaper
-    info!("{}: Initialising display buffer", function_name!());
-    let mut display = Display3in7::default();
+pub async fn display_task(
+    mut spi_device: DisplaySpiDevice,
+    pin_busy: SharedPin<Input<'static>>,
+    pin_data_cmd: SharedPin<Output<'static>>,
+    pin_reset: Output<'static>,
+) {
+    let display_config = DisplayConfig::new();
+
+    // Initialise the panel, and its buffer to draw on
+    info!("{}: Initialising display panel", function_name!());
+    let mut panel = DisplayPanel::init(&mut spi_device, pin_busy, pin_data_cmd, pin_reset)
+        .expect("Display: eink initalise error"); // Force unwrap, as there is nothing that can be done if this errors out
+
+    // Fonts and icons scaled to the resolution of the panel
+    let scale = Scale::of(panel.buffer().bounding_box().size);
+    // Strings of the language selected by cargo feature, with any overrides
+    let strings = Strings::new(StringsConfig::new());
+    let mut styles = DisplayStyles::new(
+        scale,
+        display_config.theme,
+        display_config.color_scheme,
+        strings,
+    );
 
-    // Landscape mode, USB plug to the right
-    display.set_rotation(DisplayRotation::Rotate270);
+    // Grayscale buffer, only when enabled, as it is twice the size
+    if display_config.grayscale && !GRAYSCALE_SUPPORTED {
+        warn!(
+            "{}: Grayscale is not supported by this panel, rendering in monochrome",
+            function_name!()
+        );
+    }
+    let mut grayscale = (display_config.grayscale && GRAYSCALE_SUPPORTED).then(|| {
+        info!("{}: Initialising grayscale buffer", function_name!());
+        let mut gray_display = Gray4Display::new();
+        gray_display.set_rotation(gray4::ROTATION);
+        Grayscale {
+            display: gray_display,
+            epd: Gray4Epd::new(BusyTimeout::new(pin_busy, gray4::BUSY_HIGH), pin_data_cmd),
+            styles: DisplayStyles::gray(
+                scale,
+                display_config.theme,
+                display_config.color_scheme,
+                strings,
+            ),
+        }
+    });
 
     // Render splash drawing
     info!("{}: Rendering splash drawing", function_name!());
-    show_splash(&mut display, &mut epd_driver, &mut spi_device)
+    show_splash(&mut panel, &mut spi_device, &styles)
         .unwrap_or_else(|_| error!("{}: Failed to show splash", function_name!()));
     info!(
         "{}: Display updated with splash and ready for use",
         function_name!()
     );
 
-    let styles = DisplayStyles::new();
+    // Without a quick waveform every refresh is full, so planned and counted as such
+    let mut refresh_planner = RefreshPlanner::new(DisplayConfig {
+        full_refresh_interval: if QUICK_REFRESH_SUPPORTED {
+            display_config.full_refresh_interval
+        } else {
+            0
+        },
+        ..display_config
+    });
+    // The system state or boot progress last shown, only redrawn when it changes
+    let mut shown_state = None;
+    let mut shown_progress = None;
 
     // Main update loop
     loop {
         // Handle scheduled sleep
         if !SCHEDULE.is_active() {
-            // Clear display if about to enter sleep mode
-            let _ = display
-                .clear(styles.colors.bg)
-                .map_err(|_| DisplayError::RenderingFailed);
-
-            epd_driver
-                .update_and_display_frame(&mut spi_device, &mut display.buffer(), &mut Delay)
-                .expect("Display: Failed to update display with splash");
+            // Show the sleep screen, rather than a blank board that looks dead
+            let update = {
+                let update = UPDATE.lock().await;
+                (*update).clone()
+            }; // Release lock
+            show_sleep(&mut panel, &mut spi_device, &styles, &update)
+                .unwrap_or_else(|_| error!("{}: Failed to show sleep screen", function_name!()));
+
+            // The panel holds the sleep screen without power, through the inactive hours,
+            // and is woken by the next refresh
+            info!("{}: Putting the panel to sleep", function_name!());
+            panel.sleep(&mut spi_device).unwrap_or_else(|e| {
+                error!(
+                    "{}: Failed to put the panel to sleep: {}",
+                    function_name!(),
+                    e
+                )
+            });
+            refresh_planner.invalidate();
+            shown_state = None;
+            shown_progress = None;
 
             SCHEDULE.wait_until_active().await;
         }
 
+        // Deep sleep until the next refresh
+        if display_config.sleep_between_refreshes {
+            panel.sleep(&mut spi_device).unwrap_or_else(|e| {
+                error!(
+                    "{}: Failed to put the panel to sleep: {}",
+                    function_name!(),
+                    e
+                )
+            });
+        }
+
         // Acquire lock to read data update
         info!("{}: Wait for signal...", function_name!());
         NOTIFY.wait().await;
 
+        // Show the system state in place of the update, until predictions are fetched
+        let state = system::current();
+        if state.is_starting() {
+            // The boot checklist, with a partial refresh as each step completes
+            let progress = system::boot_progress();
+            if shown_progress != Some(progress) {
+                info!("{}: Showing boot progress {}", function_name!(), progress);
+                let refresh = match shown_progress {
+                    Some(_) => Refresh::Partial,
+                    None => Refresh::Full,
+                };
+                // Left unshown if it fails, to try again with the next signal
+                match show_boot(&mut panel, &mut spi_device, &styles, progress, refresh) {
+                    Ok(()) => shown_progress = Some(progress),
+                    Err(_) => error!("{}: Failed to show boot progress", function_name!()),
+                }
+                shown_state = None;
+                refresh_planner.invalidate();
+            }
+            continue;
+        } else if state != SystemState::Ready {
+            if shown_state != Some(state) {
+                info!("{}: Showing system state {}", function_name!(), state);
+                match show_state(&mut panel, &mut spi_device, &styles, state) {
+                    Ok(()) => shown_state = Some(state),
+                    Err(_) => error!("{}: Failed to show system state", function_name!()),
+                }
+                shown_progress = None;
+                refresh_planner.invalidate();
+            }
+            continue;
+        }
+        shown_state = None;
+        shown_progress = None;
+
+        // The theme, or large type, as toggled by the button
+        let theme = button::theme(display_config.theme);
+        if theme != styles.theme {
+            info!("{}: Switching to the {} theme", function_name!(), theme);
+            styles.theme = theme;
+            if let Some(grayscale) = grayscale.as_mut() {
+                grayscale.styles.theme = theme;
+            }
+            // A different layout, so fully refreshed
+            refresh_planner.invalidate();
+        }
+
         // Get update
         let update = {
             let update = UPDATE.lock().await;
@@ -113,299 +272,204 @@ pub async fn display_task(mut epd_driver: DisplayDriver, mut spi_device: Display
 
         // Show update on display
         info!("{}: Signal received! Showing update...", function_name!());
-        let _ = display
-            .clear(styles.colors.bg)
-            .map_err(|_| DisplayError::RenderingFailed);
-        show_update(&mut display, &mut epd_driver, &mut spi_device, update)
-            .unwrap_or_else(|_| error!("{}: Failed to show update", function_name!()));
-        info!("{}: Finished rendering update", function_name!());
+        show_update(
+            &mut panel,
+            &mut spi_device,
+            &styles,
+            grayscale.as_mut(),
+            &mut refresh_planner,
+            update,
+        )
+        .unwrap_or_else(|_| {
+            // Unknown what is shown, so fully refreshed next
+            error!("{}: Failed to show update", function_name!());
+            refresh_planner.invalidate();
+        });
+        info!(
+            "{}: Finished rendering update, refreshes so far {}",
+            function_name!(),
+            refresh_planner.counts()
+        );
     }
 }
 
 /// Draw and render the splash to the epaper display
 #[named]
 fn show_splash(
-    display: &mut Display3in7,
-    epd_driver: &mut DisplayDriver,
+    panel: &mut DisplayPanel,
     spi_device: &mut DisplaySpiDevice,
+    styles: &DisplayStyles<Color>,
 ) -> Result<(), DisplayError> {
-    let styles = DisplayStyles::new();
-
-    info!("{}: Clearing display", function_name!());
-
-    display
-        .clear(styles.colors.bg)
-        .map_err(|_| DisplayError::RenderingFailed)?;
-
     info!("{}: Drawing splash", function_name!());
 
-    // Draw title
-    styles
-        .splash_font
-        .render_aligned(
-            "its3mile/london-pi-tube",
-            Point::new(
-                display.bounding_box().size.width as i32 / 2,
-                display.bounding_box().size.height as i32 / 2,
-            ),
-            VerticalPosition::Center,
-            HorizontalAlignment::Center,
-            FontColor::Transparent(styles.colors.fg),
-            display,
-        )
-        .map_err(|_| DisplayError::RenderingFailed)?;
+    draw_splash(panel.buffer(), styles)?;
 
-    epd_driver
-        .update_and_display_frame(spi_device, &mut display.buffer(), &mut Delay)
-        .expect("Display: Failed to update display with splash");
+    panel
+        .flush(spi_device, Refresh::Full)
+        .map_err(refresh_failed)?;
 
     Ok(())
 }
 
-/// Draw and render the update to the epaper display
+/// Draw and render the boot progress to the epaper display
 #[named]
-fn show_update(
-    display: &mut Display3in7,
-    epd_driver: &mut DisplayDriver,
+fn show_boot(
+    panel: &mut DisplayPanel,
     spi_device: &mut DisplaySpiDevice,
-    update: Update,
+    styles: &DisplayStyles<Color>,
+    progress: BootProgress,
+    refresh: Refresh,
 ) -> Result<(), DisplayError> {
-    let styles = DisplayStyles::new();
-
-    info!("{}: Clearing display", function_name!());
+    info!("{}: Drawing boot progress", function_name!());
 
-    display
-        .clear(styles.colors.bg)
-        .map_err(|_| DisplayError::RenderingFailed)?;
+    draw_boot(panel.buffer(), styles, progress)?;
 
-    info!("{}: Drawing update header", function_name!());
+    panel.flush(spi_device, refresh).map_err(refresh_failed)?;
 
-    // Format header tightly on one or two lines
-    let mut header_content = String::<128>::new();
-    let _ = write!(
-        &mut header_content,
-        "{} Line - {}\n{}",
-        update.line_name, update.station_name, update.platform_name
-    );
+    Ok(())
+}
 
-    // Adjusted Y position to 24 to fix the top-clipping issue
-    styles
-        .header_font
-        .render_aligned(
-            header_content.as_str(),
-            display.bounding_box().top_left + Point::new(10, 24),
-            VerticalPosition::Baseline,
-            HorizontalAlignment::Left,
-            FontColor::Transparent(styles.colors.fg),
-            display,
-        )
-        .map_err(|_| DisplayError::RenderingFailed)?;
+/// Draw and render the screen for a system state to the epaper display
+#[named]
+fn show_state(
+    panel: &mut DisplayPanel,
+    spi_device: &mut DisplaySpiDevice,
+    styles: &DisplayStyles<Color>,
+    state: SystemState,
+) -> Result<(), DisplayError> {
+    info!("{}: Drawing system state", function_name!());
 
-    info!("{}: Drawing update arrivals", function_name!());
+    let link = state.troubleshooting_link(TroubleshootingConfig::new().page);
+    draw_state(panel.buffer(), styles, state, link.as_deref())?;
 
-    // Pushed down to Y = 100 to comfortably clear the header block
-    let mut pos = display.bounding_box().top_left + Point::new(10, 100);
+    panel
+        .flush(spi_device, Refresh::Full)
+        .map_err(refresh_failed)?;
 
-    for (idx, arrival) in update.arrivals.iter().enumerate() {
-        // Guard against screen overflow (leave space for footer)
-        if pos.y > 245 {
-            break;
-        }
+    Ok(())
+}
 
-        // Time to station
-        let mut time_to_station = String::<16>::new();
-        if (arrival.time_to_station as f32 / 60.0) < 1.0 {
-            let _ = write!(&mut time_to_station, "<1 min");
-        } else if (arrival.time_to_station as f32 / 60.0) < 2.0 {
-            let _ = write!(&mut time_to_station, "<2 mins");
-        } else {
-            let _ = write!(
-                &mut time_to_station,
-                "{} mins",
-                arrival.time_to_station / 60
-            );
-        }
+/// Draw and render the sleep screen to the epaper display
+#[named]
+fn show_sleep(
+    panel: &mut DisplayPanel,
+    spi_device: &mut DisplaySpiDevice,
+    styles: &DisplayStyles<Color>,
+    update: &Update,
+) -> Result<(), DisplayError> {
+    let (hour, minute, _) = SCHEDULE.active_at();
+    let mut resumes_at = String::<8>::new();
+    let _ = write!(&mut resumes_at, "{:02}:{:02}", hour, minute);
 
-        styles
-            .time_font
-            .render_aligned(
-                time_to_station.as_str(),
-                pos,
-                VerticalPosition::Baseline,
-                HorizontalAlignment::Left,
-                FontColor::Transparent(styles.colors.fg),
-                display,
-            )
-            .map_err(|_| DisplayError::RenderingFailed)?;
-
-        // Destination name
-        // Offset expanded to 128px to safely clear the countdowns
-        let destination_pos = pos + Point::new(128, 0);
-        let destination_name = first_two_words(&arrival.destination_name);
-
-        styles
-            .bold_text_font
-            .render_aligned(
-                destination_name,
-                destination_pos,
-                VerticalPosition::Baseline,
-                HorizontalAlignment::Left,
-                FontColor::Transparent(styles.colors.fg),
-                display,
-            )
-            .map_err(|_| DisplayError::RenderingFailed)?;
-
-        // Move vertical cursor down to clear the font line
-        pos.y += 32;
-
-        // Current location, for first arrival only
-        if idx == 0 {
-            // Shifted to line up with destination name
-            let location_pos = Point::new(140, pos.y);
-
-            styles
-                .regular_text_font
-                .render_aligned(
-                    arrival.current_location.as_str(),
-                    location_pos,
-                    VerticalPosition::Baseline,
-                    HorizontalAlignment::Left,
-                    FontColor::Transparent(styles.colors.fg),
-                    display,
-                )
-                .map_err(|_| DisplayError::RenderingFailed)?;
+    let (date, slept_at) = WALL_CLOCK.lock(|cell| {
+        let clock = cell.borrow();
+        (clock.current_london_date(), clock.current_london())
+    });
 
-            // Small vertical pad to clear the location line
-            pos.y += 36;
-        } else {
-            pos.y += 12;
-        }
-    }
+    info!(
+        "{}: Drawing sleep screen, resuming at {}",
+        function_name!(),
+        resumes_at.as_str()
+    );
+    let sleep = Sleep {
+        resumes_at: &resumes_at,
+        date,
+        slept_at: slept_at.as_deref(),
+    };
+    draw_sleep(panel.buffer(), styles, update, &sleep)?;
+
+    // Always a monochrome full refresh, which leaves no ghosting overnight
+    panel
+        .flush(spi_device, Refresh::Full)
+        .map_err(refresh_failed)?;
 
-    // Bottom left, line status indicator
-    // Anchor position for the footer status icon (Bottom Left)
-    let icon_pos = display.bounding_box().top_left + Point::new(4, 228);
+    Ok(())
+}
 
-    match update.line_status.as_str() {
-        s if s.contains("Severe") || s.contains("Suspended") => {
-            let icon = icons::size48px::emojis::EmojiSad::new(BinaryColor::On);
-            Image::new(&icon, icon_pos)
-                .draw(&mut display.color_converted())
-                .ok();
-        }
-        s if s.contains("Minor") || s.contains("Delay") => {
-            let icon = icons::size48px::emojis::EmojiQuite::new(BinaryColor::On);
-            Image::new(&icon, icon_pos)
-                .draw(&mut display.color_converted())
-                .ok();
-        }
-        "Good Service" => {
-            let icon = icons::size48px::emojis::Emoji::new(BinaryColor::On);
-            Image::new(&icon, icon_pos)
-                .draw(&mut display.color_converted())
-                .ok();
-        }
-        _ => {
-            // Fallback warning triangle for anything else
-            let icon = icons::size48px::emojis::EmojiPuzzled::new(BinaryColor::On);
-            Image::new(&icon, icon_pos)
-                .draw(&mut display.color_converted())
-                .ok();
-        }
-    }
+/// Grayscale buffer and renderer, for full refreshes in grayscale mode
+pub struct Grayscale {
+    pub display: Gray4Display,
+    pub epd: DisplayGrayEpd,
+    pub styles: DisplayStyles<Gray2>,
+}
 
-    // Bottom right, last updated
-    let current_time = WALL_CLOCK.lock(|cell| {
+/// Draw and render the update to the epaper display, unless the frame is unchanged
+#[named]
+fn show_update(
+    panel: &mut DisplayPanel,
+    spi_device: &mut DisplaySpiDevice,
+    styles: &DisplayStyles<Color>,
+    grayscale: Option<&mut Grayscale>,
+    refresh_planner: &mut RefreshPlanner,
+    update: Update,
+) -> Result<(), DisplayError> {
+    // Last updated, as of the proxy generating the update, so that a stale update shows its age
+    let current_time = match update.generated_at {
+        Some(generated_at) => Some(format_london_time(generated_at)),
         // borrow() gives us the &WallClock safely
-        cell.borrow().current_london()
-    });
-
-    match current_time {
-        Some(t) => {
-            info!("{}: The current time is {}", function_name!(), &t);
-
-            // Place at bottom right (Canvas: 480x280)
-            let footer_pos = Point::new(470, 270);
-
-            styles
-                .tiny_font
-                .render_aligned(
-                    t.as_str(),
-                    footer_pos,
-                    VerticalPosition::Baseline,
-                    HorizontalAlignment::Right, // Anchor from right edge
-                    FontColor::Transparent(styles.colors.fg),
-                    display,
-                )
-                .map_err(|_| DisplayError::RenderingFailed)?;
-        }
+        None => WALL_CLOCK.lock(|cell| cell.borrow().current_london()),
+    };
+    match &current_time {
+        Some(t) => info!("{}: Last updated at {}", function_name!(), t),
         None => warn!(
             "{}: Unable to get current time - clock syncing pending...",
             function_name!()
         ),
     }
+    let updated_at = current_time.as_ref().map(|t| t.as_str());
+
+    // The monochrome frame is always drawn, as the base for partial refreshes
+    info!("{}: Drawing update", function_name!());
+    let refresh = refresh_planner.plan(&update);
+    draw_update(panel.buffer(), styles, &update, refresh, updated_at)?;
+
+    // Nothing to refresh if the frame is the same as that shown
+    if !refresh_planner.needs_refresh(refresh, panel.frame()) {
+        info!(
+            "{}: Frame unchanged, skipping {} refresh",
+            function_name!(),
+            refresh
+        );
+        return Ok(());
+    }
 
-    info!("{}: Rendering update", function_name!());
-
-    epd_driver
-        .update_and_display_frame(spi_device, &mut display.buffer(), &mut Delay)
-        .expect("Display: Failed to render with update data");
-
-    Ok(())
-}
-
-/// Colors used for the display
-pub struct DisplayColors {
-    pub bg: Color,
-    pub fg: Color,
-}
-
-// Structs to hold style and color information
-pub struct DisplayStyles {
-    pub colors: DisplayColors,
-    pub header_font: FontRenderer,
-    pub time_font: FontRenderer,
-    pub bold_text_font: FontRenderer,
-    pub regular_text_font: FontRenderer,
-    pub splash_font: FontRenderer,
-    pub tiny_font: FontRenderer,
-}
+    info!(
+        "{}: Rendering update with {} refresh",
+        function_name!(),
+        refresh
+    );
 
-impl DisplayStyles {
-    pub const fn new() -> Self {
-        let colors = DisplayColors {
-            bg: Color::White,
-            fg: Color::Black,
-        };
-
-        Self {
-            colors,
-            header_font: FontRenderer::new::<fonts::u8g2_font_helvB14_tf>(),
-            time_font: FontRenderer::new::<fonts::u8g2_font_logisoso24_tf>(),
-            bold_text_font: FontRenderer::new::<fonts::u8g2_font_logisoso24_tf>(),
-            regular_text_font: FontRenderer::new::<fonts::u8g2_font_helvR14_tf>(),
-            splash_font: FontRenderer::new::<fonts::u8g2_font_logisoso32_tf>(),
-            tiny_font: FontRenderer::new::<fonts::u8g2_font_helvR10_tf>(),
+    match (refresh, grayscale) {
+        (Refresh::Full, Some(grayscale)) => {
+            info!("{}: Drawing grayscale update", function_name!());
+            draw_update(
+                &mut grayscale.display,
+                &grayscale.styles,
+                &update,
+                refresh,
+                updated_at,
+            )?;
+
+            // Rendered directly, so the panel must first be woken
+            panel.wake(spi_device).map_err(refresh_failed)?;
+            grayscale
+                .epd
+                .render(spi_device, &mut Delay, &grayscale.display, panel.frame())
+                .map_err(|e| {
+                    error!("{}: Grayscale refresh failed: {}", function_name!(), e);
+                    DisplayError::RefreshFailed
+                })?;
+            panel.check_busy().map_err(refresh_failed)?;
         }
+        _ => panel.flush(spi_device, refresh).map_err(refresh_failed)?,
     }
-}
 
-/// Represents any error that may happen during display operations.
-#[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
-pub enum DisplayError {
-    /// An error occurred while rendering data
-    RenderingFailed,
+    Ok(())
 }
 
-pub fn first_two_words(s: &str) -> &str {
-    let mut space_count = 0;
-    for (i, c) in s.char_indices() {
-        if c == ' ' {
-            space_count += 1;
-            if space_count == 2 {
-                return &s[..i];
-            }
-        }
-    }
-    s
+/// The error for a failed refresh, logging the cause
+#[named]
+fn refresh_failed(e: PanelError) -> DisplayError {
+    error!("{}: Panel refresh failed: {}", function_name!(), e);
+    DisplayError::RefreshFailed
 }
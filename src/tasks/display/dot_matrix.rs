@@ -0,0 +1,393 @@
+//! Dot-matrix departure board theme
+//!
+//! An alternative layout of the update, styled after the dot-matrix
+//! indicators on the platforms, see `Theme::DotMatrix`. The screen is drawn
+//! onto a grid of dots, each a block of pixels with a gap around it, in a
+//! 5x7 pixel font, so each character is made up of dots as on the boards.
+//!
+//! +-------------------------------------------------------------+
+//! | Platform 1                                         12:34:56 |
+//! | 1 Upminster                                          2 mins |
+//! | 2 Wimbledon                                          6 mins |
+//! | 3 Ealing Broadway                                   11 mins |
+//! |                                                             |
+//! | Between Barking and East Ham                                |
+//! +-------------------------------------------------------------+
+//!
+//! The bottom line alternates each minute between the current location of
+//! the next train and the line status, as the boards scroll between them.
+//!
+//! The grid is sized in dots, from the scale of the panel, and the rows are
+//! packed into it as in `layout`, but measured in dots rather than pixels.
+//!
+use core::fmt::Write as _;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{Circle, PrimitiveStyle, Rectangle};
+use heapless::String;
+use u8g2_fonts::{
+    FontRenderer, fonts,
+    types::{FontColor, HorizontalAlignment, VerticalPosition},
+};
+
+use crate::models::update::{Fault, Update};
+use crate::tasks::display::draw::{DisplayError, DisplayStyles};
+use crate::tasks::display::fit::{Fit, Measure, fit_text};
+use crate::tasks::display::layout::{FontMetrics, Scale};
+use crate::tasks::display::strings::fill;
+
+// Font of the boards, each pixel drawn as a dot
+const FONT: FontRenderer = FontRenderer::new::<fonts::u8g2_font_5x7_tf>();
+
+// Space around the edge of the grid, and between the columns, in dots
+const DOT_MARGIN: i32 = 2;
+
+// Vertical space between rows, in dots
+const DOT_ROW_GAP: i32 = 2;
+
+// Pitch from which dots are drawn round, below which they are too small to tell from squares
+const ROUND_DOT_PITCH: u32 = 5;
+
+/// Pitch of the dots in pixels, so the grid is around 150 dots wide on each panel
+const fn pitch(scale: Scale) -> u32 {
+    match scale {
+        Scale::Small => 2,
+        Scale::Regular => 3,
+        Scale::Large => 5,
+    }
+}
+
+/// Draw the update as a dot-matrix departure board
+///
+/// Always redrawn in full, as the grid leaves no header to keep for a partial refresh.
+pub fn draw_update<D>(
+    display: &mut D,
+    styles: &DisplayStyles<D::Color>,
+    update: &Update,
+    updated_at: Option<&str>,
+) -> Result<(), DisplayError>
+where
+    D: DrawTarget,
+{
+    display
+        .clear(styles.colors.bg)
+        .map_err(|_| DisplayError::RenderingFailed)?;
+
+    let mut dots = Dots::new(display, pitch(styles.scale));
+
+    // Unlit dots, only visible with grey levels
+    if styles.colors.band != styles.colors.bg {
+        dots.clear(styles.colors.band)
+            .map_err(|_| DisplayError::RenderingFailed)?;
+    }
+
+    let bounds = dots.bounding_box();
+    let width = bounds.size.width as i32 - 2 * DOT_MARGIN;
+    let line_height = FontMetrics::of(&FONT).height(1);
+    let right = bounds.size.width as i32 - DOT_MARGIN;
+    let bottom = bounds.size.height as i32 - DOT_MARGIN - line_height;
+    let fg = styles.colors.fg;
+
+    // Platform on the top line, with the last updated time on the right, unless the clock is not
+    // yet synced
+    let updated_width = match updated_at {
+        Some(t) => {
+            draw_text(
+                &mut dots,
+                t,
+                Point::new(right, DOT_MARGIN),
+                Align::Right,
+                fg,
+            )?;
+            FONT.text_width(t) + DOT_MARGIN
+        }
+        None => 0,
+    };
+    let platform = fit_text(
+        &update.platform_name,
+        width - updated_width,
+        &[&FONT],
+        Fit::Ellipsis,
+    );
+    draw_first_line(
+        &mut dots,
+        &platform.lines,
+        Point::new(DOT_MARGIN, DOT_MARGIN),
+        fg,
+    )?;
+
+    // Rows below, clear of the bottom line
+    let top = DOT_MARGIN + line_height + 2 * DOT_ROW_GAP;
+    let rows = Rectangle::new(
+        Point::new(DOT_MARGIN, top),
+        Size::new(width as u32, (bottom - DOT_ROW_GAP - top).max(0) as u32),
+    );
+
+    match update.fault {
+        Some(fault) => draw_fault(&mut dots, styles, rows, fault)?,
+        None => draw_arrivals(&mut dots, styles, rows, update)?,
+    }
+
+    let bottom_line = bottom_line(styles, update, updated_at);
+    let bottom_line = fit_text(&bottom_line, width, &[&FONT], Fit::Ellipsis);
+    draw_first_line(
+        &mut dots,
+        &bottom_line.lines,
+        Point::new(DOT_MARGIN, bottom),
+        fg,
+    )?;
+
+    Ok(())
+}
+
+/// Draw a numbered row for each arrival that fits, with the time right aligned
+fn draw_arrivals<D>(
+    dots: &mut Dots<D>,
+    styles: &DisplayStyles<D::Color>,
+    rows: Rectangle,
+    update: &Update,
+) -> Result<(), DisplayError>
+where
+    D: DrawTarget,
+{
+    let line_height = FontMetrics::of(&FONT).height(1);
+    let right = rows.top_left.x + rows.size.width as i32;
+    let bottom = rows.top_left.y + rows.size.height as i32;
+    let fg = styles.colors.fg;
+
+    // Numbers in a column as wide as the widest, as the rows are at most nine
+    let number_width = FONT.text_width("9") + FONT.text_width(" ");
+
+    let mut y = rows.top_left.y;
+    for (idx, arrival) in update.arrivals.iter().enumerate() {
+        if y + line_height > bottom {
+            break;
+        }
+
+        let mut number = String::<4>::new();
+        let _ = write!(&mut number, "{}", idx + 1);
+        draw_text(
+            dots,
+            &number,
+            Point::new(rows.top_left.x, y),
+            Align::Left,
+            fg,
+        )?;
+
+        // Time to station, then the destination in what remains to its left
+        let time_to_station = styles
+            .strings
+            .time_to_station(arrival.time_to_station, arrival.uncertain);
+        draw_text(
+            dots,
+            &time_to_station,
+            Point::new(right, y),
+            Align::Right,
+            fg,
+        )?;
+
+        let max_width =
+            rows.size.width as i32 - number_width - FONT.text_width(&time_to_station) - DOT_MARGIN;
+        let destination_name = fit_text(
+            &arrival.destination_name,
+            max_width,
+            &[&FONT],
+            Fit::Abbreviate,
+        );
+        draw_first_line(
+            dots,
+            &destination_name.lines,
+            Point::new(rows.top_left.x + number_width, y),
+            fg,
+        )?;
+
+        y += line_height + DOT_ROW_GAP;
+    }
+
+    Ok(())
+}
+
+/// Draw a fault message in place of the arrivals, wrapped to fit
+fn draw_fault<D>(
+    dots: &mut Dots<D>,
+    styles: &DisplayStyles<D::Color>,
+    rows: Rectangle,
+    fault: Fault,
+) -> Result<(), DisplayError>
+where
+    D: DrawTarget,
+{
+    let fault_content = match fault {
+        Fault::SchemaMismatch { expected, found } => {
+            fill::<96>(styles.strings.schema_mismatch, &[&found, &expected])
+        }
+    };
+
+    let fitted = fit_text(&fault_content, rows.size.width as i32, &[&FONT], Fit::Wrap);
+    let line_height = FontMetrics::of(&FONT).height(1) + DOT_ROW_GAP;
+    for (idx, line) in fitted.lines.iter().enumerate() {
+        draw_text(
+            dots,
+            line,
+            rows.top_left + Point::new(0, line_height * idx as i32),
+            Align::Left,
+            styles.colors.fg,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// The current location of the next train on even minutes, and the line status on odd minutes,
+/// or if the location or the time is not known
+fn bottom_line<C>(
+    styles: &DisplayStyles<C>,
+    update: &Update,
+    updated_at: Option<&str>,
+) -> String<128> {
+    // Minutes of "HH:MM:SS"
+    let minute = updated_at.and_then(|t| t.get(3..5)?.parse::<u32>().ok());
+    let location = update
+        .arrivals
+        .first()
+        .map(|arrival| arrival.current_location.as_str())
+        .filter(|location| !location.is_empty());
+
+    match (minute, location, update.fault) {
+        (Some(minute), Some(location), None) if minute % 2 == 0 => {
+            let mut line = String::new();
+            let _ = line.push_str(location);
+            line
+        }
+        _ => fill(
+            styles.strings.line_status,
+            &[&styles.strings.line_status_text(&update.line_status)],
+        ),
+    }
+}
+
+/// Horizontal alignment of text to its position
+#[derive(Copy, Clone, Debug)]
+enum Align {
+    Left,
+    Right,
+}
+
+/// Draw a line of text, top aligned at the position
+fn draw_text<D>(
+    dots: &mut Dots<D>,
+    text: &str,
+    pos: Point,
+    align: Align,
+    color: D::Color,
+) -> Result<(), DisplayError>
+where
+    D: DrawTarget,
+{
+    let alignment = match align {
+        Align::Left => HorizontalAlignment::Left,
+        Align::Right => HorizontalAlignment::Right,
+    };
+
+    FONT.render_aligned(
+        text,
+        pos,
+        VerticalPosition::Top,
+        alignment,
+        FontColor::Transparent(color),
+        dots,
+    )
+    .map_err(|_| DisplayError::RenderingFailed)?;
+
+    Ok(())
+}
+
+/// Draw the first of the fitted lines, if any
+fn draw_first_line<D, S>(
+    dots: &mut Dots<D>,
+    lines: &[S],
+    pos: Point,
+    color: D::Color,
+) -> Result<(), DisplayError>
+where
+    D: DrawTarget,
+    S: AsRef<str>,
+{
+    match lines.first() {
+        Some(line) => draw_text(dots, line.as_ref(), pos, Align::Left, color),
+        None => Ok(()),
+    }
+}
+
+/// A grid of dots, drawing each of its pixels as a dot on the display below, centred on it
+pub struct Dots<'a, D> {
+    display: &'a mut D,
+    // Distance between the dots, in pixels of the display
+    pitch: u32,
+    // Top left of the grid, on the display
+    origin: Point,
+    size: Size,
+}
+
+impl<'a, D> Dots<'a, D>
+where
+    D: DrawTarget,
+{
+    pub fn new(display: &'a mut D, pitch: u32) -> Self {
+        let bounds = display.bounding_box();
+        let size = bounds.size / pitch;
+        let origin = bounds.top_left + (bounds.size - size * pitch) / 2;
+
+        Self {
+            display,
+            pitch,
+            origin,
+            size,
+        }
+    }
+
+    /// Area of the dot on the display, leaving a gap of a pixel to the next
+    fn dot(&self, point: Point) -> Rectangle {
+        Rectangle::new(
+            self.origin + point * self.pitch as i32,
+            Size::new_equal(self.pitch.saturating_sub(1).max(1)),
+        )
+    }
+}
+
+impl<D> OriginDimensions for Dots<'_, D> {
+    fn size(&self) -> Size {
+        self.size
+    }
+}
+
+impl<D> DrawTarget for Dots<'_, D>
+where
+    D: DrawTarget,
+{
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bounds = self.bounding_box();
+
+        for Pixel(point, color) in pixels {
+            if !bounds.contains(point) {
+                continue;
+            }
+
+            let dot = self.dot(point);
+            if self.pitch >= ROUND_DOT_PITCH {
+                Circle::new(dot.top_left, dot.size.width)
+                    .into_styled(PrimitiveStyle::with_fill(color))
+                    .draw(self.display)?;
+            } else {
+                self.display.fill_solid(&dot, color)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,1126 @@
+//! Display drawing
+//!
+//! Draws the screens onto any `DrawTarget`, separate to rendering them to the
+//! epaper display, so that the same drawing code can run on the host, see the
+//! `simulator` crate.
+//!
+//! N.B. This is included by the simulator, so must not depend on the
+//! embassy runtime or log with defmt.
+//!
+use core::fmt::Write as _;
+use defmt::Format;
+use embedded_graphics::image::Image;
+use embedded_graphics::pixelcolor::Gray2;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{Line, Polyline, PrimitiveStyle, Rectangle};
+use embedded_iconoir::prelude::*;
+use epd_waveshare::color::Color;
+use heapless::String;
+use u8g2_fonts::{
+    FontRenderer, fonts,
+    types::{FontColor, HorizontalAlignment, VerticalPosition},
+};
+
+use crate::models::boot::BootProgress;
+use crate::models::prediction::Prediction;
+use crate::models::system::SystemState;
+use crate::models::update::{Fault, Update};
+use crate::tasks::display::dot_matrix;
+use crate::tasks::display::fit::{Fit, FitText, Fitted, Measure, fit_text};
+use crate::tasks::display::gray4::SmoothEdges;
+use crate::tasks::display::large_type;
+use crate::tasks::display::layout::{FontMetrics, Layout, MARGIN, ROW_GAP, Rows, Scale};
+use crate::tasks::display::qr::{Ecc, QrCode};
+use crate::tasks::display::refresh::Refresh;
+use crate::tasks::display::strings::{Strings, fill};
+use crate::tasks::display::typeface;
+
+// Smallest module of a QR code, in pixels, that can be read from the panel by a phone
+const QR_MIN_MODULE_SIZE: u32 = 2;
+
+/// Draw the splash
+pub fn draw_splash<D>(display: &mut D, styles: &DisplayStyles<D::Color>) -> Result<(), DisplayError>
+where
+    D: DrawTarget,
+{
+    display
+        .clear(styles.colors.bg)
+        .map_err(|_| DisplayError::RenderingFailed)?;
+
+    // Draw title
+    styles
+        .splash_font
+        .render_aligned(
+            "its3mile/london-pi-tube",
+            display.bounding_box().center(),
+            VerticalPosition::Center,
+            HorizontalAlignment::Center,
+            FontColor::Transparent(styles.colors.fg),
+            display,
+        )
+        .map_err(|_| DisplayError::RenderingFailed)?;
+
+    Ok(())
+}
+
+/// Draw the update, or only the arrivals and footer for a partial refresh, in the layout of the theme
+pub fn draw_update<D>(
+    display: &mut D,
+    styles: &DisplayStyles<D::Color>,
+    update: &Update,
+    refresh: Refresh,
+    updated_at: Option<&str>,
+) -> Result<(), DisplayError>
+where
+    D: DrawTarget + SmoothEdges,
+{
+    match styles.theme {
+        Theme::Classic => {}
+        Theme::DotMatrix => return dot_matrix::draw_update(display, styles, update, updated_at),
+        Theme::LargeType => return large_type::draw_update(display, styles, update),
+    }
+
+    let layout = Layout::new(display.bounding_box(), styles);
+
+    match refresh {
+        Refresh::Full => {
+            display
+                .clear(styles.colors.bg)
+                .map_err(|_| DisplayError::RenderingFailed)?;
+
+            draw_header(display, styles, &layout, update)?;
+        }
+        Refresh::Partial => {
+            // The header is unchanged, so is left as drawn
+            display
+                .fill_solid(&layout.body(), styles.colors.bg)
+                .map_err(|_| DisplayError::RenderingFailed)?;
+        }
+    }
+
+    draw_arrivals(display, styles, &layout, update)?;
+    draw_footer(display, styles, &layout, update, updated_at)?;
+
+    Ok(())
+}
+
+/// Draw the boot progress, as a checklist of the steps completed so far
+pub fn draw_boot<D>(
+    display: &mut D,
+    styles: &DisplayStyles<D::Color>,
+    progress: BootProgress,
+) -> Result<(), DisplayError>
+where
+    D: DrawTarget,
+{
+    display
+        .clear(styles.colors.bg)
+        .map_err(|_| DisplayError::RenderingFailed)?;
+
+    let bounds = display.bounding_box();
+    let mut rows = Rows::new(Rectangle::new(
+        bounds.top_left + Point::new(MARGIN, MARGIN),
+        Size::new(
+            bounds.size.width.saturating_sub(2 * MARGIN as u32),
+            bounds.size.height.saturating_sub(2 * MARGIN as u32),
+        ),
+    ));
+
+    let strings = &styles.strings;
+    draw_row(
+        display,
+        &mut rows,
+        &styles.header_font,
+        strings.starting_up,
+        Fit::Ellipsis,
+        styles.colors.fg,
+    )?;
+
+    let mut address = String::<32>::new();
+    let _ = match progress.address {
+        Some([a, b, c, d]) => write!(
+            &mut address,
+            "{} {}.{}.{}.{}",
+            strings.boot_address, a, b, c, d
+        ),
+        None => write!(&mut address, "{}", strings.boot_address),
+    };
+    let steps = [
+        (strings.boot_wifi, progress.wifi),
+        (address.as_str(), progress.address.is_some()),
+        (strings.boot_time, progress.time),
+        (strings.boot_tfl, progress.tfl),
+    ];
+
+    // Each step is a tick once complete, or an ellipsis until then, then its label
+    let font = &styles.regular_text_font;
+    let height = FontMetrics::of(font).height(1);
+    for (label, done) in steps {
+        let Some(row) = rows.next(height) else {
+            break;
+        };
+
+        if done {
+            draw_tick(
+                display,
+                Rectangle::new(row.top_left, Size::new(height as u32, height as u32)),
+                styles.colors.fg,
+            )?;
+        } else {
+            font.render_aligned(
+                "...",
+                row.top_left,
+                VerticalPosition::Top,
+                HorizontalAlignment::Left,
+                FontColor::Transparent(styles.colors.dim),
+                display,
+            )
+            .map_err(|_| DisplayError::RenderingFailed)?;
+        }
+
+        font.render_aligned(
+            label,
+            row.top_left + Point::new(height + MARGIN, 0),
+            VerticalPosition::Top,
+            HorizontalAlignment::Left,
+            FontColor::Transparent(styles.colors.fg),
+            display,
+        )
+        .map_err(|_| DisplayError::RenderingFailed)?;
+    }
+
+    Ok(())
+}
+
+/// Draw a tick mark filling the square
+fn draw_tick<D>(display: &mut D, area: Rectangle, color: D::Color) -> Result<(), DisplayError>
+where
+    D: DrawTarget,
+{
+    let size = area.size.height as i32;
+    let points = [
+        area.top_left + Point::new(size / 8, size / 2),
+        area.top_left + Point::new(size * 3 / 8, size * 3 / 4),
+        area.top_left + Point::new(size * 7 / 8, size / 4),
+    ];
+
+    Polyline::new(&points)
+        .into_styled(PrimitiveStyle::with_stroke(color, (size as u32 / 8).max(1)))
+        .draw(display)
+        .map_err(|_| DisplayError::RenderingFailed)
+}
+
+/// Draw the screen for a system state, explaining why there are no arrivals, with a QR code of
+/// the link to its troubleshooting notes, if any
+pub fn draw_state<D>(
+    display: &mut D,
+    styles: &DisplayStyles<D::Color>,
+    state: SystemState,
+    link: Option<&str>,
+) -> Result<(), DisplayError>
+where
+    D: DrawTarget + SmoothEdges,
+{
+    display
+        .clear(styles.colors.bg)
+        .map_err(|_| DisplayError::RenderingFailed)?;
+
+    // Icon on the left, with the title, explanation and error code beside it
+    let bounds = display.bounding_box();
+    let icon_size = styles.scale.icon_size() as i32;
+    let fg = styles.colors.fg;
+    let icon_pos = bounds.top_left + Point::new(MARGIN, MARGIN);
+    match state {
+        SystemState::JoiningWifi { .. } | SystemState::NoIp => draw_icon(
+            display,
+            styles.scale,
+            icons::size18px::connectivity::WifiOff::new(fg),
+            icons::size48px::connectivity::WifiOff::new(fg),
+            icon_pos,
+        ),
+        SystemState::TlsFailed | SystemState::AuthFailed { .. } => draw_icon(
+            display,
+            styles.scale,
+            icons::size18px::security::Lock::new(fg),
+            icons::size48px::security::Lock::new(fg),
+            icon_pos,
+        ),
+        SystemState::RequestFailed { .. } => draw_icon(
+            display,
+            styles.scale,
+            icons::size18px::cloud::CloudError::new(fg),
+            icons::size48px::cloud::CloudError::new(fg),
+            icon_pos,
+        ),
+        SystemState::NoPredictions => draw_icon(
+            display,
+            styles.scale,
+            icons::size18px::transport::Train::new(fg),
+            icons::size48px::transport::Train::new(fg),
+            icon_pos,
+        ),
+        _ => draw_icon(
+            display,
+            styles.scale,
+            icons::size18px::emojis::EmojiPuzzled::new(fg),
+            icons::size48px::emojis::EmojiPuzzled::new(fg),
+            icon_pos,
+        ),
+    }
+
+    // QR code in the bottom right corner, its quiet zone clear of the text
+    let qr_width = match link {
+        Some(link) => draw_qr_code(
+            display,
+            link,
+            corner_square(bounds, bounds.size.height / 2),
+            styles.colors.qr(styles.scheme),
+        )?,
+        None => 0,
+    } as i32;
+
+    let text_offset = MARGIN + icon_size + MARGIN;
+    let mut rows = Rows::new(Rectangle::new(
+        bounds.top_left + Point::new(text_offset, MARGIN),
+        Size::new(
+            (bounds.size.width as i32 - text_offset - MARGIN.max(qr_width)).max(0) as u32,
+            (bounds.size.height as i32 - 2 * MARGIN).max(0) as u32,
+        ),
+    ));
+
+    let (title, explanation) = state_message(&styles.strings, state);
+    draw_row(
+        display,
+        &mut rows,
+        &styles.header_font,
+        title,
+        Fit::Ellipsis,
+        fg,
+    )?;
+    draw_row(
+        display,
+        &mut rows,
+        &styles.regular_text_font,
+        explanation,
+        Fit::Wrap,
+        fg,
+    )?;
+
+    if let Some(code) = state.code() {
+        let error_code = fill::<48>(styles.strings.error_code, &[&code]);
+        draw_row(
+            display,
+            &mut rows,
+            &styles.tiny_font,
+            &error_code,
+            Fit::Ellipsis,
+            styles.colors.dim,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Square in the bottom right corner of the area
+fn corner_square(area: Rectangle, size: u32) -> Rectangle {
+    let size = size.min(area.size.width).min(area.size.height);
+    Rectangle::new(
+        area.top_left + (area.size - Size::new_equal(size)),
+        Size::new_equal(size),
+    )
+}
+
+/// Draw the data as a QR code in the bottom right corner of the square, as large as fits,
+/// returning the width drawn, or 0 if too long to encode, or too small to be read
+fn draw_qr_code<D>(
+    display: &mut D,
+    data: &str,
+    square: Rectangle,
+    (dark, light): (D::Color, D::Color),
+) -> Result<u32, DisplayError>
+where
+    D: DrawTarget,
+{
+    let module_size_of = |qr: &QrCode| square.size.width / qr.drawn_size(1);
+    let Ok(low) = QrCode::encode(data.as_bytes(), Ecc::Low) else {
+        return Ok(0);
+    };
+    // The higher error correction level, where it can be drawn as large
+    let qr = match QrCode::encode(data.as_bytes(), Ecc::Medium) {
+        Ok(medium) if module_size_of(&medium) == module_size_of(&low) => medium,
+        _ => low,
+    };
+
+    let module_size = module_size_of(&qr);
+    if module_size < QR_MIN_MODULE_SIZE {
+        return Ok(0);
+    }
+
+    let size = Size::new_equal(qr.drawn_size(module_size));
+    qr.draw(
+        display,
+        square.top_left + (square.size - size),
+        module_size,
+        dark,
+        light,
+    )
+    .map_err(|_| DisplayError::RenderingFailed)?;
+
+    Ok(size.width)
+}
+
+/// Title and explanation of a system state
+fn state_message(strings: &Strings, state: SystemState) -> (&'static str, &'static str) {
+    match state {
+        SystemState::Starting => (strings.starting, strings.starting_detail),
+        SystemState::JoiningWifi { status: None } => {
+            (strings.joining_wifi, strings.joining_wifi_detail)
+        }
+        SystemState::JoiningWifi { status: Some(_) } => {
+            (strings.wifi_failed, strings.wifi_failed_detail)
+        }
+        SystemState::NoIp => (strings.no_ip, strings.no_ip_detail),
+        SystemState::DnsFailed => (strings.dns_failed, strings.dns_failed_detail),
+        SystemState::TlsFailed => (strings.tls_failed, strings.tls_failed_detail),
+        SystemState::AuthFailed { .. } => (strings.auth_failed, strings.auth_failed_detail),
+        SystemState::RequestFailed { .. } => {
+            (strings.request_failed, strings.request_failed_detail)
+        }
+        SystemState::NoPredictions => (strings.no_predictions, strings.no_predictions_detail),
+        SystemState::Ready => (strings.ready, strings.ready_detail),
+    }
+}
+
+/// Details of the inactive hours, for the sleep screen
+pub struct Sleep<'a> {
+    // Time the board resumes, e.g. "06:30"
+    pub resumes_at: &'a str,
+    // Today's date as (month, day, weekday from Sunday), unless the clock is not yet synced
+    pub date: Option<(u32, u32, u32)>,
+    // Time the board went to sleep, that the line status is as of
+    pub slept_at: Option<&'a str>,
+}
+
+/// Draw the sleep screen, left up through the inactive hours
+///
+/// Only drawn in monochrome, with a full refresh, so that it holds cleanly on
+/// the panel with the power removed.
+pub fn draw_sleep<D>(
+    display: &mut D,
+    styles: &DisplayStyles<D::Color>,
+    update: &Update,
+    sleep: &Sleep,
+) -> Result<(), DisplayError>
+where
+    D: DrawTarget + SmoothEdges,
+{
+    let layout = Layout::new(display.bounding_box(), styles);
+
+    display
+        .clear(styles.colors.bg)
+        .map_err(|_| DisplayError::RenderingFailed)?;
+
+    draw_header(display, styles, &layout, update)?;
+
+    let mut rows = Rows::new(layout.arrivals);
+
+    // When the board resumes, in the countdown font, then the date
+    let resumes_at = fill::<48>(styles.strings.back_at, &[&sleep.resumes_at]);
+    draw_row(
+        display,
+        &mut rows,
+        &styles.time_font,
+        &resumes_at,
+        Fit::Ellipsis,
+        styles.colors.fg,
+    )?;
+    if let Some((month, day, weekday)) = sleep.date {
+        draw_row(
+            display,
+            &mut rows,
+            &styles.regular_text_font,
+            &styles.strings.format_date(month, day, weekday),
+            Fit::Ellipsis,
+            styles.colors.fg,
+        )?;
+    }
+
+    // Line status as of going to sleep, also shown by the footer icon
+    let line_status = fill::<96>(
+        styles.strings.line_status,
+        &[&styles.strings.line_status_text(&update.line_status)],
+    );
+    draw_row(
+        display,
+        &mut rows,
+        &styles.regular_text_font,
+        &line_status,
+        Fit::Ellipsis,
+        styles.colors.fg,
+    )?;
+
+    // Planned closures, omitted if there are none or they are not known
+    if !update.closures.is_empty() {
+        draw_row(
+            display,
+            &mut rows,
+            &styles.regular_text_font,
+            styles.strings.planned_closures,
+            Fit::Ellipsis,
+            styles.colors.fg,
+        )?;
+        for closure in update.closures.iter() {
+            draw_row(
+                display,
+                &mut rows,
+                &styles.regular_text_font,
+                closure,
+                Fit::Wrap,
+                styles.colors.dim,
+            )?;
+        }
+    }
+
+    draw_footer(display, styles, &layout, update, sleep.slept_at)?;
+
+    Ok(())
+}
+
+/// Draw the text fitted as the next row, unless the region is already full
+fn draw_row<D>(
+    display: &mut D,
+    rows: &mut Rows,
+    font: &FontRenderer,
+    text: &str,
+    fit: Fit,
+    color: D::Color,
+) -> Result<(), DisplayError>
+where
+    D: DrawTarget,
+{
+    let max_width = rows.width();
+    let fitted = fit_text(text, max_width, &[font], fit);
+
+    match rows.next(FontMetrics::of(font).height(fitted.lines.len() as i32)) {
+        Some(row) => draw_lines(display, font, &fitted.lines, row.top_left, color),
+        None => Ok(()),
+    }
+}
+
+/// Draw the line, station and platform header
+fn draw_header<D>(
+    display: &mut D,
+    styles: &DisplayStyles<D::Color>,
+    layout: &Layout,
+    update: &Update,
+) -> Result<(), DisplayError>
+where
+    D: DrawTarget + SmoothEdges,
+{
+    // Band behind the header, only visible with grey levels
+    display
+        .fill_solid(&layout.header, styles.colors.band)
+        .map_err(|_| DisplayError::RenderingFailed)?;
+
+    // Line and station on the first line, platform on the second
+    let title = fill::<128>(
+        styles.strings.header,
+        &[&update.line_name.as_str(), &update.station_name.as_str()],
+    );
+
+    let max_width = layout.header.size.width as i32 - 2 * MARGIN;
+    let title_fonts = [&styles.header_font, &styles.header_small_font];
+    let title = fit_text(&title, max_width, &title_fonts, Fit::StepDown);
+    let platform = fit_text(
+        &update.platform_name,
+        max_width,
+        &[&styles.header_font],
+        Fit::Ellipsis,
+    );
+
+    let pos = layout.header.top_left + Point::new(MARGIN, MARGIN);
+    draw_lines(
+        display,
+        title_fonts[title.font],
+        &title.lines,
+        pos,
+        styles.colors.fg,
+    )?;
+    draw_lines(
+        display,
+        &styles.header_font,
+        &platform.lines,
+        pos + Point::new(0, FontMetrics::of(&styles.header_font).line_height),
+        styles.colors.fg,
+    )?;
+
+    Ok(())
+}
+
+/// Draw fitted lines of text, top aligned at the position
+fn draw_lines<D>(
+    display: &mut D,
+    font: &FontRenderer,
+    lines: &[FitText],
+    pos: Point,
+    color: D::Color,
+) -> Result<(), DisplayError>
+where
+    D: DrawTarget,
+{
+    let line_height = FontMetrics::of(font).line_height;
+
+    for (idx, line) in lines.iter().enumerate() {
+        font.render_aligned(
+            line.as_str(),
+            pos + Point::new(0, line_height * idx as i32),
+            VerticalPosition::Top,
+            HorizontalAlignment::Left,
+            FontColor::Transparent(color),
+            display,
+        )
+        .map_err(|_| DisplayError::RenderingFailed)?;
+    }
+
+    Ok(())
+}
+
+/// Draw the arrivals, or a fault in their place
+fn draw_arrivals<D>(
+    display: &mut D,
+    styles: &DisplayStyles<D::Color>,
+    layout: &Layout,
+    update: &Update,
+) -> Result<(), DisplayError>
+where
+    D: DrawTarget + SmoothEdges,
+{
+    // Faults are drawn in place of the arrivals, as the data cannot be trusted
+    let arrivals: &[Prediction] = match update.fault {
+        Some(fault) => {
+            draw_fault(display, styles, layout, fault)?;
+            &[]
+        }
+        None => &update.arrivals,
+    };
+
+    // Each row is the time and destination, then the current location for the first arrival
+    let primary_height = FontMetrics::of(&styles.time_font)
+        .height(1)
+        .max(FontMetrics::of(&styles.bold_text_font).height(1));
+    let column_width = layout.arrivals.size.width as i32 - layout.destination_offset;
+
+    let mut rows = Rows::new(layout.arrivals);
+
+    for (idx, arrival) in arrivals.iter().enumerate() {
+        // Wrapped onto a second line if needed, so fitted before sizing the row
+        let mut location = match idx {
+            0 => fit_text(
+                &arrival.current_location,
+                column_width,
+                &[&styles.regular_text_font],
+                Fit::Wrap,
+            ),
+            _ => Fitted::default(),
+        };
+        let row_height = match location.lines.len() {
+            0 => primary_height,
+            lines => {
+                primary_height
+                    + ROW_GAP
+                    + FontMetrics::of(&styles.regular_text_font).height(lines as i32)
+            }
+        };
+
+        // Stop once the arrivals region is full, omitting the location where only that is too tall
+        let row = match rows.next(row_height) {
+            Some(row) => row,
+            None if !location.lines.is_empty() => {
+                location = Fitted::default();
+                match rows.next(primary_height) {
+                    Some(row) => row,
+                    None => break,
+                }
+            }
+            None => break,
+        };
+
+        // Time to station, marking trains missing from the latest response as approximate
+        let time_to_station = styles
+            .strings
+            .time_to_station(arrival.time_to_station, arrival.uncertain);
+
+        let time_bounds = styles
+            .time_font
+            .render_aligned(
+                time_to_station.as_str(),
+                row.top_left,
+                VerticalPosition::Top,
+                HorizontalAlignment::Left,
+                FontColor::Transparent(styles.colors.fg),
+                display,
+            )
+            .map_err(|_| DisplayError::RenderingFailed)?;
+        if let Some(time_bounds) = time_bounds {
+            display.smooth_edges(time_bounds, styles.scheme);
+        }
+
+        // Destination name, in the column clear of the widest countdown
+        let destination_pos = row.top_left + Point::new(layout.destination_offset, 0);
+        let destination_name = fit_text(
+            &arrival.destination_name,
+            column_width,
+            &[&styles.bold_text_font],
+            Fit::Abbreviate,
+        );
+
+        let destination_bounds = styles
+            .bold_text_font
+            .render_aligned(
+                destination_name
+                    .lines
+                    .first()
+                    .map_or("", |line| line.as_str()),
+                destination_pos,
+                VerticalPosition::Top,
+                HorizontalAlignment::Left,
+                FontColor::Transparent(styles.colors.fg),
+                display,
+            )
+            .map_err(|_| DisplayError::RenderingFailed)?;
+        if let Some(destination_bounds) = destination_bounds {
+            display.smooth_edges(destination_bounds, styles.scheme);
+        }
+
+        // Current location, for first arrival only
+        // Lined up with the destination name, below the time and destination
+        // Secondary information, dimmed where there are grey levels
+        draw_lines(
+            display,
+            &styles.regular_text_font,
+            &location.lines,
+            destination_pos + Point::new(0, primary_height + ROW_GAP),
+            styles.colors.dim,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Draw the line status, proxy message and last updated footer
+fn draw_footer<D>(
+    display: &mut D,
+    styles: &DisplayStyles<D::Color>,
+    layout: &Layout,
+    update: &Update,
+    updated_at: Option<&str>,
+) -> Result<(), DisplayError>
+where
+    D: DrawTarget + SmoothEdges,
+{
+    // Bottom left, line status indicator, vertically centred in the footer
+    let icon_size = styles.scale.icon_size() as i32;
+    let icon_pos = layout.footer.top_left
+        + Point::new(
+            MARGIN / 2,
+            (layout.footer.size.height as i32 - icon_size) / 2,
+        );
+
+    draw_status_icon(display, styles, &update.line_status, icon_pos);
+
+    // Bottom right, last updated, unless the clock is not yet synced
+    let baseline = layout.footer.size.height as i32 - MARGIN;
+    if let Some(t) = updated_at {
+        let footer_pos =
+            layout.footer.top_left + Point::new(layout.footer.size.width as i32 - MARGIN, baseline);
+
+        styles
+            .tiny_font
+            .render_aligned(
+                t,
+                footer_pos,
+                VerticalPosition::Baseline,
+                HorizontalAlignment::Right, // Anchor from right edge
+                FontColor::Transparent(styles.colors.fg),
+                display,
+            )
+            .map_err(|_| DisplayError::RenderingFailed)?;
+    }
+
+    // Between them, the first message from the proxy, e.g. that the update is cached
+    let left = MARGIN / 2 + icon_size + MARGIN;
+    let right = layout.footer.size.width as i32
+        - MARGIN
+        - updated_at.map_or(0, |t| styles.tiny_font.text_width(t) + MARGIN);
+    if let Some(message) = update.messages.first()
+        && right > left
+    {
+        let fitted = fit_text(message, right - left, &[&styles.tiny_font], Fit::Ellipsis);
+
+        for line in fitted.lines.iter() {
+            styles
+                .tiny_font
+                .render_aligned(
+                    line.as_str(),
+                    layout.footer.top_left + Point::new(left, baseline),
+                    VerticalPosition::Baseline,
+                    HorizontalAlignment::Left,
+                    FontColor::Transparent(styles.colors.dim),
+                    display,
+                )
+                .map_err(|_| DisplayError::RenderingFailed)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Draw the icon of the line status, at the position
+pub fn draw_status_icon<D>(
+    display: &mut D,
+    styles: &DisplayStyles<D::Color>,
+    line_status: &str,
+    pos: Point,
+) where
+    D: DrawTarget,
+{
+    let fg = styles.colors.fg;
+    match line_status {
+        s if s.contains("Severe") || s.contains("Suspended") => draw_icon(
+            display,
+            styles.scale,
+            icons::size18px::emojis::EmojiSad::new(fg),
+            icons::size48px::emojis::EmojiSad::new(fg),
+            pos,
+        ),
+        s if s.contains("Minor") || s.contains("Delay") => draw_icon(
+            display,
+            styles.scale,
+            icons::size18px::emojis::EmojiQuite::new(fg),
+            icons::size48px::emojis::EmojiQuite::new(fg),
+            pos,
+        ),
+        "Good Service" => draw_icon(
+            display,
+            styles.scale,
+            icons::size18px::emojis::Emoji::new(fg),
+            icons::size48px::emojis::Emoji::new(fg),
+            pos,
+        ),
+        // Fallback for anything else
+        _ => draw_icon(
+            display,
+            styles.scale,
+            icons::size18px::emojis::EmojiPuzzled::new(fg),
+            icons::size48px::emojis::EmojiPuzzled::new(fg),
+            pos,
+        ),
+    }
+}
+
+/// Draw the icon in the size for the scale, small or large
+fn draw_icon<D, S, L>(display: &mut D, scale: Scale, small: S, large: L, pos: Point)
+where
+    D: DrawTarget,
+    S: ImageDrawable<Color = D::Color>,
+    L: ImageDrawable<Color = D::Color>,
+{
+    match scale {
+        Scale::Small => Image::new(&small, pos).draw(display).ok(),
+        _ => Image::new(&large, pos).draw(display).ok(),
+    };
+}
+
+/// Draw a fault message in the centre of the arrivals area
+fn draw_fault<D>(
+    display: &mut D,
+    styles: &DisplayStyles<D::Color>,
+    layout: &Layout,
+    fault: Fault,
+) -> Result<(), DisplayError>
+where
+    D: DrawTarget,
+{
+    let fault_content = match fault {
+        Fault::SchemaMismatch { expected, found } => {
+            fill::<96>(styles.strings.schema_mismatch, &[&found, &expected])
+        }
+    };
+
+    styles
+        .regular_text_font
+        .render_aligned(
+            fault_content.as_str(),
+            layout.arrivals.center(),
+            VerticalPosition::Center,
+            HorizontalAlignment::Center,
+            FontColor::Transparent(styles.colors.fg),
+            display,
+        )
+        .map_err(|_| DisplayError::RenderingFailed)?;
+
+    Ok(())
+}
+
+/// Colors used for the display
+pub struct DisplayColors<C> {
+    pub bg: C,
+    // Text, lines and icons, which are drawn only where set, so take the color of the scheme
+    pub fg: C,
+    // Secondary information
+    pub dim: C,
+    // Background of the header
+    pub band: C,
+}
+
+impl DisplayColors<Color> {
+    pub const fn mono(scheme: ColorScheme) -> Self {
+        match scheme {
+            ColorScheme::Light => Self {
+                bg: Color::White,
+                fg: Color::Black,
+                dim: Color::Black,
+                band: Color::White,
+            },
+            ColorScheme::Dark => Self {
+                bg: Color::Black,
+                fg: Color::White,
+                dim: Color::White,
+                band: Color::Black,
+            },
+        }
+    }
+}
+
+impl DisplayColors<Gray2> {
+    pub const fn gray(scheme: ColorScheme) -> Self {
+        match scheme {
+            ColorScheme::Light => Self {
+                bg: Gray2::WHITE,
+                fg: Gray2::BLACK,
+                dim: Gray2::new(1),
+                band: Gray2::new(2),
+            },
+            ColorScheme::Dark => Self {
+                bg: Gray2::BLACK,
+                fg: Gray2::WHITE,
+                dim: Gray2::new(2),
+                band: Gray2::new(1),
+            },
+        }
+    }
+}
+
+impl<C: Copy> DisplayColors<C> {
+    /// Dark and light modules of the QR codes, dark on light in either scheme, as not every
+    /// reader scans them inverted
+    fn qr(&self, scheme: ColorScheme) -> (C, C) {
+        match scheme {
+            ColorScheme::Light => (self.fg, self.bg),
+            ColorScheme::Dark => (self.bg, self.fg),
+        }
+    }
+}
+
+/// Colors of every screen
+#[derive(Copy, Clone, Debug, Default, Format, Eq, PartialEq)]
+pub enum ColorScheme {
+    /// Black on white
+    #[default]
+    Light,
+    /// White on black, inverted
+    Dark,
+}
+
+/// Layout of the update screen
+#[derive(Copy, Clone, Debug, Default, Format, Eq, PartialEq)]
+pub enum Theme {
+    /// Times and destinations under a header of the line and station, with a status icon
+    #[default]
+    Classic,
+    /// As the dot-matrix departure boards on the platforms, see `dot_matrix`
+    DotMatrix,
+    /// Only the next train, in the largest type that fits, see `large_type`
+    LargeType,
+}
+
+// Structs to hold style and color information
+pub struct DisplayStyles<C> {
+    pub colors: DisplayColors<C>,
+    pub scale: Scale,
+    pub theme: Theme,
+    pub scheme: ColorScheme,
+    pub header_font: FontRenderer,
+    pub header_small_font: FontRenderer,
+    pub time_font: FontRenderer,
+    pub bold_text_font: FontRenderer,
+    pub regular_text_font: FontRenderer,
+    pub splash_font: FontRenderer,
+    pub tiny_font: FontRenderer,
+    pub strings: Strings,
+}
+
+impl DisplayStyles<Color> {
+    pub const fn new(scale: Scale, theme: Theme, scheme: ColorScheme, strings: Strings) -> Self {
+        Self::with_colors(scale, theme, scheme, strings, DisplayColors::mono(scheme))
+    }
+}
+
+impl DisplayStyles<Gray2> {
+    pub const fn gray(scale: Scale, theme: Theme, scheme: ColorScheme, strings: Strings) -> Self {
+        Self::with_colors(scale, theme, scheme, strings, DisplayColors::gray(scheme))
+    }
+}
+
+impl<C> DisplayStyles<C> {
+    const fn with_colors(
+        scale: Scale,
+        theme: Theme,
+        scheme: ColorScheme,
+        strings: Strings,
+        colors: DisplayColors<C>,
+    ) -> Self {
+        match scale {
+            Scale::Small => Self {
+                colors,
+                scale,
+                theme,
+                scheme,
+                header_font: FontRenderer::new::<typeface::Bold10>(),
+                header_small_font: FontRenderer::new::<typeface::Bold8>(),
+                time_font: FontRenderer::new::<fonts::u8g2_font_logisoso16_tf>(),
+                bold_text_font: FontRenderer::new::<typeface::Display16>(),
+                regular_text_font: FontRenderer::new::<typeface::Regular8>(),
+                splash_font: FontRenderer::new::<fonts::u8g2_font_logisoso20_tf>(),
+                tiny_font: FontRenderer::new::<typeface::Regular8>(),
+                strings,
+            },
+            Scale::Regular => Self {
+                colors,
+                scale,
+                theme,
+                scheme,
+                header_font: FontRenderer::new::<typeface::Bold14>(),
+                header_small_font: FontRenderer::new::<typeface::Bold10>(),
+                time_font: FontRenderer::new::<fonts::u8g2_font_logisoso24_tf>(),
+                bold_text_font: FontRenderer::new::<typeface::Display24>(),
+                regular_text_font: FontRenderer::new::<typeface::Regular14>(),
+                splash_font: FontRenderer::new::<fonts::u8g2_font_logisoso32_tf>(),
+                tiny_font: FontRenderer::new::<typeface::Regular10>(),
+                strings,
+            },
+            Scale::Large => Self {
+                colors,
+                scale,
+                theme,
+                scheme,
+                header_font: FontRenderer::new::<typeface::Bold24>(),
+                header_small_font: FontRenderer::new::<typeface::Bold18>(),
+                time_font: FontRenderer::new::<fonts::u8g2_font_logisoso38_tf>(),
+                bold_text_font: FontRenderer::new::<typeface::Display38>(),
+                regular_text_font: FontRenderer::new::<typeface::Regular24>(),
+                splash_font: FontRenderer::new::<fonts::u8g2_font_logisoso50_tf>(),
+                tiny_font: FontRenderer::new::<typeface::Regular14>(),
+                strings,
+            },
+        }
+    }
+}
+
+/// Represents any error that may happen during display operations.
+#[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
+pub enum DisplayError {
+    /// An error occurred while rendering data
+    RenderingFailed,
+    /// The panel failed to refresh, or did not finish in time
+    RefreshFailed,
+}
+
+/// Draw a panic message wrapped to the width, and as a QR code where it fits
+pub fn draw_panic<D>(
+    display: &mut D,
+    styles: &DisplayStyles<D::Color>,
+    message: &str,
+) -> Result<(), DisplayError>
+where
+    D: DrawTarget,
+{
+    let fg = styles.colors.fg;
+    display
+        .clear(styles.colors.bg)
+        .map_err(|_| DisplayError::RenderingFailed)?;
+
+    // Title, with a rule under it
+    let bounds = display.bounding_box();
+    let width = (bounds.size.width as i32 - 2 * MARGIN).max(0);
+    let title_height = FontMetrics::of(&styles.header_font).height(1);
+    let title = fit_text(
+        styles.strings.panic_title,
+        width,
+        &[&styles.header_font],
+        Fit::Ellipsis,
+    );
+    draw_lines(
+        display,
+        &styles.header_font,
+        &title.lines,
+        bounds.top_left + Point::new(MARGIN, MARGIN),
+        fg,
+    )?;
+
+    let rule_y = MARGIN + title_height + ROW_GAP / 2;
+    Line::new(
+        bounds.top_left + Point::new(MARGIN, rule_y),
+        bounds.top_left + Point::new(MARGIN + width, rule_y),
+    )
+    .into_styled(PrimitiveStyle::with_stroke(fg, 1))
+    .draw(display)
+    .map_err(|_| DisplayError::RenderingFailed)?;
+
+    // The crash report as a QR code in the bottom right corner, to be read off the panel
+    let qr_width = draw_qr_code(
+        display,
+        message,
+        corner_square(bounds, bounds.size.height / 2),
+        styles.colors.qr(styles.scheme),
+    )? as i32;
+
+    // The message beside the QR code, each paragraph wrapped a line at a time, until the panel
+    // is full, as the QR code holds the whole report
+    let font = &styles.regular_text_font;
+    let metrics = FontMetrics::of(font);
+    let max_width = (bounds.size.width as i32 - MARGIN - MARGIN.max(qr_width)).max(0);
+    let bottom = bounds.size.height as i32 - MARGIN;
+    let mut y = MARGIN + title_height + ROW_GAP;
+
+    for paragraph in message.split('\n') {
+        let mut rest = paragraph.trim();
+        while !rest.is_empty() {
+            if y + metrics.height(1) > bottom {
+                return Ok(());
+            }
+
+            let fitted = fit_text(rest, max_width, &[font], Fit::Wrap);
+            let Some(line) = fitted.lines.first() else {
+                break;
+            };
+            draw_lines(
+                display,
+                font,
+                &fitted.lines[..1],
+                bounds.top_left + Point::new(MARGIN, y),
+                fg,
+            )?;
+            y += metrics.line_height;
+
+            rest = match rest.strip_prefix(line.as_str()) {
+                Some(rest) => rest,
+                // Cut short, as a word too long for the width
+                None => rest.split_once(' ').map_or("", |(_, rest)| rest),
+            }
+            .trim_start();
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,184 @@
+//! Measured text fitting
+//!
+//! Fits text into a width, measured as actually rendered by the font, with a
+//! strategy chosen per field:
+//!
+//! - `Ellipsis`: cut the text short, ending with "..."
+//! - `Abbreviate`: abbreviate the name as a whole (e.g. "Kensington (Olympia)"
+//!   to "Olympia"), or else common station name words (e.g. "Street" to "St"),
+//!   from the last word backwards, until it fits, then cut short, with the
+//!   abbreviations of `names`
+//! - `StepDown`: use the first of a list of fonts, largest first, in which the
+//!   text fits, or cut it short in the smallest
+//! - `Wrap`: wrap onto a second line at a word boundary, cutting the second
+//!   line short if needed
+//!
+use embedded_graphics::prelude::*;
+use heapless::{String, Vec};
+use u8g2_fonts::FontRenderer;
+use u8g2_fonts::types::VerticalPosition;
+
+use crate::filters::names::{ABBREVIATIONS, WORD_ABBREVIATIONS};
+
+pub const FIT_TEXT_SIZE: usize = 96;
+pub const FIT_MAX_LINES: usize = 2;
+
+pub type FitText = String<FIT_TEXT_SIZE>;
+
+const ELLIPSIS: &str = "...";
+
+/// Strategy to fit text that is too wide
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Fit {
+    Ellipsis,
+    Abbreviate,
+    StepDown,
+    Wrap,
+}
+
+/// Text that can be measured as it would be rendered
+pub trait Measure {
+    /// Width of the text, from the origin to the right most drawn pixel
+    fn text_width(&self, text: &str) -> i32;
+}
+
+impl Measure for FontRenderer {
+    fn text_width(&self, text: &str) -> i32 {
+        self.get_rendered_dimensions(text, Point::zero(), VerticalPosition::Baseline)
+            .ok()
+            .and_then(|dimensions| dimensions.bounding_box)
+            .map(|bounds| bounds.top_left.x + bounds.size.width as i32)
+            .unwrap_or_default()
+    }
+}
+
+/// Fitted text, as lines to draw in the chosen font
+#[derive(Clone, Debug, Default)]
+pub struct Fitted {
+    pub lines: Vec<FitText, FIT_MAX_LINES>,
+    // Index of the font to draw in
+    pub font: usize,
+}
+
+/// Fit the text into the width, with the fonts to choose from (largest first)
+pub fn fit_text<M: Measure>(text: &str, max_width: i32, fonts: &[&M], fit: Fit) -> Fitted {
+    let mut fitted = Fitted::default();
+    let Some(font) = fonts.first() else {
+        return fitted;
+    };
+
+    match fit {
+        Fit::Ellipsis => {
+            let _ = fitted.lines.push(ellipsis(text, max_width, *font));
+        }
+        Fit::Abbreviate => {
+            let _ = fitted.lines.push(abbreviate(text, max_width, *font));
+        }
+        Fit::StepDown => {
+            // Fall back to the smallest font, cut short
+            fitted.font = fonts
+                .iter()
+                .position(|font| font.text_width(text) <= max_width)
+                .unwrap_or(fonts.len() - 1);
+            let _ = fitted
+                .lines
+                .push(ellipsis(text, max_width, fonts[fitted.font]));
+        }
+        Fit::Wrap => {
+            fitted.lines = wrap(text, max_width, *font);
+        }
+    }
+
+    fitted
+}
+
+/// Cut the text short at a character boundary, to fit with an ellipsis
+fn ellipsis<M: Measure>(text: &str, max_width: i32, font: &M) -> FitText {
+    if font.text_width(text) <= max_width {
+        return truncated(text);
+    }
+
+    let mut end = text.len().min(FIT_TEXT_SIZE - ELLIPSIS.len());
+    loop {
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        let mut candidate = truncated(text[..end].trim_end());
+        let _ = candidate.push_str(ELLIPSIS);
+        if end == 0 || font.text_width(&candidate) <= max_width {
+            return candidate;
+        }
+        end -= 1;
+    }
+}
+
+/// Abbreviate the whole text, or else words from the last backwards, until the text fits, then
+/// cut short
+fn abbreviate<M: Measure>(text: &str, max_width: i32, font: &M) -> FitText {
+    if font.text_width(text) > max_width
+        && let Some((_, abbreviation)) = ABBREVIATIONS.iter().find(|(name, _)| *name == text)
+    {
+        return ellipsis(abbreviation, max_width, font);
+    }
+
+    let mut words: Vec<&str, 16> = text.split(' ').take(16).collect();
+
+    for idx in (0..words.len()).rev() {
+        if font.text_width(&join(&words)) <= max_width {
+            break;
+        }
+        if let Some((_, abbreviation)) = WORD_ABBREVIATIONS
+            .iter()
+            .find(|(word, _)| *word == words[idx])
+        {
+            words[idx] = abbreviation;
+        }
+    }
+
+    ellipsis(&join(&words), max_width, font)
+}
+
+/// Wrap onto a second line at the last word boundary that fits
+fn wrap<M: Measure>(text: &str, max_width: i32, font: &M) -> Vec<FitText, FIT_MAX_LINES> {
+    let mut lines = Vec::new();
+
+    if font.text_width(text) > max_width {
+        let split = text
+            .match_indices(' ')
+            .map(|(idx, _)| idx)
+            .rev()
+            .find(|idx| font.text_width(&text[..*idx]) <= max_width);
+
+        if let Some(split) = split {
+            let _ = lines.push(truncated(&text[..split]));
+            let _ = lines.push(ellipsis(text[split..].trim_start(), max_width, font));
+            return lines;
+        }
+    }
+
+    let _ = lines.push(ellipsis(text, max_width, font));
+    lines
+}
+
+fn join(words: &[&str]) -> FitText {
+    let mut text = FitText::new();
+    for (idx, word) in words.iter().enumerate() {
+        if idx > 0 {
+            let _ = text.push(' ');
+        }
+        let _ = text.push_str(word);
+    }
+    text
+}
+
+/// Copy as much of the text as fits the capacity, at a character boundary
+fn truncated(text: &str) -> FitText {
+    let mut end = text.len().min(FIT_TEXT_SIZE);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    let mut fitted = FitText::new();
+    let _ = fitted.push_str(&text[..end]);
+    fitted
+}
@@ -0,0 +1,284 @@
+//! Four level grayscale rendering
+//!
+//! The 3.7" panel supports four grey levels, by writing the two bits of each
+//! pixel to the two RAM banks of the controller, and refreshing with the
+//! 4-gray waveform. This is not supported by `epd-waveshare`, so the
+//! commands and waveform are taken from the Waveshare reference C driver.
+//!
+//! A grayscale refresh is a slow full refresh, so is only used for full
+//! refreshes, with the monochrome frame still used for partial refreshes.
+//!
+use defmt::Format;
+use embedded_graphics::pixelcolor::Gray2;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use embedded_hal_1::delay::DelayNs;
+use embedded_hal_1::digital::{InputPin, OutputPin};
+use embedded_hal_1::spi::SpiDevice;
+use epd_waveshare::epd3in7::{Display3in7, HEIGHT, WIDTH};
+use epd_waveshare::prelude::DisplayRotation;
+
+use crate::tasks::display::draw::{ColorScheme, DisplayColors};
+
+// Landscape, as the monochrome buffer of the 3.7" panel
+pub const ROTATION: DisplayRotation = DisplayRotation::Rotate270;
+
+// Size of a single bit plane, in the native (portrait) orientation of the panel
+const ROW_BYTES: usize = WIDTH as usize / 8;
+const PLANE_SIZE: usize = ROW_BYTES * HEIGHT as usize;
+
+// Controller commands
+const SET_RAM_X_ADDRESS_COUNTER: u8 = 0x4E;
+const SET_RAM_Y_ADDRESS_COUNTER: u8 = 0x4F;
+const WRITE_RAM_BW: u8 = 0x24;
+const WRITE_RAM_RED: u8 = 0x26;
+const WRITE_LUT_REGISTER: u8 = 0x32;
+const DISPLAY_UPDATE_SEQUENCE_SETTING: u8 = 0x22;
+const MASTER_ACTIVATION: u8 = 0x20;
+
+// Update sequence to load the written waveform and refresh
+const UPDATE_SEQUENCE_GRAY: u8 = 0xC7;
+
+// Level of BUSY whilst the controller is refreshing
+pub const BUSY_HIGH: bool = true;
+
+// 4-gray global refresh waveform, from the Waveshare reference C driver (`lut_4Gray_GC`)
+#[rustfmt::skip]
+const LUT_4GRAY_GC: [u8; 105] = [
+    0x2A, 0x06, 0x15, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x28, 0x06, 0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x20, 0x06, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x14, 0x06, 0x28, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x02, 0x02, 0x0A, 0x00, 0x00, 0x00, 0x08, 0x08, 0x02,
+    0x00, 0x02, 0x02, 0x0A, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x22, 0x22, 0x22, 0x22, 0x22,
+];
+
+/// Draw targets that can soften the staircase edges of rendered (1-bit) fonts
+pub trait SmoothEdges {
+    /// Soften the edges of the foreground within the area, a no-op without grey levels
+    fn smooth_edges(&mut self, _area: Rectangle, _scheme: ColorScheme) {}
+}
+
+impl SmoothEdges for Display3in7 {}
+
+/// A 2-bit per pixel display buffer, held as the two bit planes written to the controller
+pub struct Gray4Display {
+    // High bit of each pixel, written to the BW RAM
+    high: [u8; PLANE_SIZE],
+    // Low bit of each pixel, written to the RED RAM
+    low: [u8; PLANE_SIZE],
+    rotation: DisplayRotation,
+}
+
+impl Gray4Display {
+    pub const fn new() -> Self {
+        Self {
+            high: [0xFF; PLANE_SIZE],
+            low: [0xFF; PLANE_SIZE],
+            rotation: DisplayRotation::Rotate0,
+        }
+    }
+
+    pub fn set_rotation(&mut self, rotation: DisplayRotation) {
+        self.rotation = rotation;
+    }
+
+    /// Get a pixel, in rotated coordinates
+    pub fn pixel(&self, point: Point) -> Option<Gray2> {
+        let (index, mask) = self.locate(point)?;
+        let high = (self.high[index] & mask != 0) as u8;
+        let low = (self.low[index] & mask != 0) as u8;
+        Some(Gray2::new(high << 1 | low))
+    }
+
+    fn set_pixel(&mut self, point: Point, color: Gray2) {
+        if let Some((index, mask)) = self.locate(point) {
+            set_bit(&mut self.high[index], mask, color.luma() & 0b10 != 0);
+            set_bit(&mut self.low[index], mask, color.luma() & 0b01 != 0);
+        }
+    }
+
+    /// Byte index and bit mask of a pixel, in rotated coordinates
+    fn locate(&self, point: Point) -> Option<(usize, u8)> {
+        let size = self.size();
+        if point.x < 0
+            || point.y < 0
+            || point.x as u32 >= size.width
+            || point.y as u32 >= size.height
+        {
+            return None;
+        }
+
+        let (x, y) = (point.x as u32, point.y as u32);
+        let (nx, ny) = match self.rotation {
+            DisplayRotation::Rotate0 => (x, y),
+            DisplayRotation::Rotate90 => (WIDTH - 1 - y, x),
+            DisplayRotation::Rotate180 => (WIDTH - 1 - x, HEIGHT - 1 - y),
+            DisplayRotation::Rotate270 => (y, HEIGHT - 1 - x),
+        };
+
+        Some((nx as usize / 8 + ny as usize * ROW_BYTES, 0x80 >> (nx % 8)))
+    }
+}
+
+fn set_bit(byte: &mut u8, mask: u8, set: bool) {
+    if set {
+        *byte |= mask;
+    } else {
+        *byte &= !mask;
+    }
+}
+
+impl OriginDimensions for Gray4Display {
+    fn size(&self) -> Size {
+        match self.rotation {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => Size::new(WIDTH, HEIGHT),
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => Size::new(HEIGHT, WIDTH),
+        }
+    }
+}
+
+impl DrawTarget for Gray4Display {
+    type Color = Gray2;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            self.set_pixel(point, color);
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let fill = |set: bool| if set { 0xFF } else { 0x00 };
+        self.high.fill(fill(color.luma() & 0b10 != 0));
+        self.low.fill(fill(color.luma() & 0b01 != 0));
+        Ok(())
+    }
+}
+
+impl SmoothEdges for Gray4Display {
+    /// Shade the background pixels in the inside corner of each foreground step a level towards
+    /// the foreground, i.e. light grey on white, or dark grey on black
+    fn smooth_edges(&mut self, area: Rectangle, scheme: ColorScheme) {
+        let DisplayColors { fg, bg, .. } = DisplayColors::gray(scheme);
+        let shade = match scheme {
+            ColorScheme::Light => Gray2::new(2),
+            ColorScheme::Dark => Gray2::new(1),
+        };
+        let is_fg = |display: &Self, point: Point| display.pixel(point) == Some(fg);
+
+        for point in area.offset(1).points() {
+            if self.pixel(point) != Some(bg) {
+                continue;
+            }
+
+            let horizontal =
+                is_fg(self, point + Point::new(-1, 0)) || is_fg(self, point + Point::new(1, 0));
+            let vertical =
+                is_fg(self, point + Point::new(0, -1)) || is_fg(self, point + Point::new(0, 1));
+            if horizontal && vertical {
+                self.set_pixel(point, shade);
+            }
+        }
+    }
+}
+
+/// Represents any error that may happen when rendering in grayscale.
+#[derive(Copy, Clone, Debug, Format, Eq, PartialEq)]
+pub enum Gray4Error {
+    /// The SPI transfer or a control pin failed
+    InterfaceFailed,
+}
+
+/// Renders a grayscale frame, sharing the control pins with the EPD driver
+///
+/// BUSY must read idle once held for too long, see `pins::BusyTimeout`, so that a hung
+/// controller is detected by the panel, as for its own waits.
+pub struct Gray4Epd<BUSY, DC> {
+    busy: BUSY,
+    dc: DC,
+}
+
+impl<BUSY: InputPin, DC: OutputPin> Gray4Epd<BUSY, DC> {
+    pub fn new(busy: BUSY, dc: DC) -> Self {
+        Self { busy, dc }
+    }
+
+    /// Render the grayscale frame, then restore the monochrome frame to the controller RAM
+    ///
+    /// Quick refreshes only drive the pixels that differ from the RAM, so it must hold the
+    /// monochrome equivalent of what is shown, for partial refreshes to follow.
+    pub fn render<SPI: SpiDevice>(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut impl DelayNs,
+        display: &Gray4Display,
+        mono: &[u8],
+    ) -> Result<(), Gray4Error> {
+        self.write_ram(spi, WRITE_RAM_BW, &display.high)?;
+        self.write_ram(spi, WRITE_RAM_RED, &display.low)?;
+
+        self.command(spi, WRITE_LUT_REGISTER, &LUT_4GRAY_GC)?;
+        self.command(
+            spi,
+            DISPLAY_UPDATE_SEQUENCE_SETTING,
+            &[UPDATE_SEQUENCE_GRAY],
+        )?;
+        self.command(spi, MASTER_ACTIVATION, &[])?;
+        self.wait_until_idle(delay)?;
+
+        self.write_ram(spi, WRITE_RAM_BW, mono)?;
+        self.write_ram(spi, WRITE_RAM_RED, mono)
+    }
+
+    fn write_ram<SPI: SpiDevice>(
+        &mut self,
+        spi: &mut SPI,
+        ram: u8,
+        data: &[u8],
+    ) -> Result<(), Gray4Error> {
+        self.command(spi, SET_RAM_X_ADDRESS_COUNTER, &[0x00, 0x00])?;
+        self.command(spi, SET_RAM_Y_ADDRESS_COUNTER, &[0x00, 0x00])?;
+        self.command(spi, ram, data)
+    }
+
+    fn command<SPI: SpiDevice>(
+        &mut self,
+        spi: &mut SPI,
+        command: u8,
+        data: &[u8],
+    ) -> Result<(), Gray4Error> {
+        self.dc.set_low().map_err(|_| Gray4Error::InterfaceFailed)?;
+        spi.write(&[command])
+            .map_err(|_| Gray4Error::InterfaceFailed)?;
+
+        if !data.is_empty() {
+            self.dc
+                .set_high()
+                .map_err(|_| Gray4Error::InterfaceFailed)?;
+            spi.write(data).map_err(|_| Gray4Error::InterfaceFailed)?;
+        }
+        Ok(())
+    }
+
+    /// Wait for the controller, which holds BUSY high whilst refreshing, or until timed out
+    fn wait_until_idle(&mut self, delay: &mut impl DelayNs) -> Result<(), Gray4Error> {
+        while self
+            .busy
+            .is_high()
+            .map_err(|_| Gray4Error::InterfaceFailed)?
+        {
+            delay.delay_ms(1);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,208 @@
+//! Large type theme
+//!
+//! An accessible layout of the update, showing only the next train, in the
+//! largest type that fits the panel, to be read from across the room or
+//! with low vision, see `Theme::LargeType`. It is drawn from the same update
+//! as the other themes, and may also be toggled by the button, see `button`.
+//!
+//! +-------------------------------------------------------------+
+//! | Upminster                                                   |
+//! |                                                             |
+//! |                      2 mins                                 |
+//! |                                                             |
+//! |                                                      (icon) |
+//! +-------------------------------------------------------------+
+//!
+//! The line status is only shown as its icon, in the bottom right corner.
+//!
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use u8g2_fonts::{
+    FontRenderer, fonts,
+    types::{FontColor, HorizontalAlignment, VerticalPosition},
+};
+
+use crate::models::update::{Fault, Update};
+use crate::tasks::display::draw::{DisplayError, DisplayStyles, draw_status_icon};
+use crate::tasks::display::fit::{Fit, Measure, fit_text};
+use crate::tasks::display::gray4::SmoothEdges;
+use crate::tasks::display::layout::{FontMetrics, MARGIN};
+use crate::tasks::display::strings::fill;
+use crate::tasks::display::typeface;
+
+// Fonts of the destination, largest first
+const DESTINATION_FONTS: [FontRenderer; 3] = [
+    FontRenderer::new::<typeface::Display38>(),
+    FontRenderer::new::<typeface::Display24>(),
+    FontRenderer::new::<typeface::Display16>(),
+];
+
+// Fonts of the time to station, largest first
+const TIME_FONTS: [FontRenderer; 6] = [
+    FontRenderer::new::<fonts::u8g2_font_logisoso58_tf>(),
+    FontRenderer::new::<fonts::u8g2_font_logisoso50_tf>(),
+    FontRenderer::new::<fonts::u8g2_font_logisoso42_tf>(),
+    FontRenderer::new::<fonts::u8g2_font_logisoso32_tf>(),
+    FontRenderer::new::<fonts::u8g2_font_logisoso24_tf>(),
+    FontRenderer::new::<fonts::u8g2_font_logisoso16_tf>(),
+];
+
+/// Draw the next train in large type
+///
+/// Always redrawn in full, as the text is sized to each update.
+pub fn draw_update<D>(
+    display: &mut D,
+    styles: &DisplayStyles<D::Color>,
+    update: &Update,
+) -> Result<(), DisplayError>
+where
+    D: DrawTarget + SmoothEdges,
+{
+    display
+        .clear(styles.colors.bg)
+        .map_err(|_| DisplayError::RenderingFailed)?;
+
+    let bounds = display.bounding_box();
+    let area = Rectangle::new(
+        bounds.top_left + Point::new(MARGIN, MARGIN),
+        Size::new(
+            bounds.size.width.saturating_sub(2 * MARGIN as u32),
+            bounds.size.height.saturating_sub(2 * MARGIN as u32),
+        ),
+    );
+
+    // Line status in the bottom right corner, clear of the time
+    let icon_size = styles.scale.icon_size();
+    let icon_pos = area.top_left + (area.size - Size::new_equal(icon_size));
+    draw_status_icon(display, styles, &update.line_status, icon_pos);
+
+    // A fault, or that there are no trains, in place of the next train
+    let width = area.size.width.saturating_sub(icon_size + MARGIN as u32);
+    let message_area = Rectangle::new(area.top_left, Size::new(width, area.size.height));
+    let arrival = match (update.fault, update.arrivals.first()) {
+        (Some(Fault::SchemaMismatch { expected, found }), _) => {
+            let message = fill::<96>(styles.strings.schema_mismatch, &[&found, &expected]);
+            return draw_message(display, styles, message_area, &message);
+        }
+        (None, None) => {
+            return draw_message(display, styles, message_area, styles.strings.no_predictions);
+        }
+        (None, Some(arrival)) => arrival,
+    };
+
+    // Destination across the top, in up to a third of the height
+    let destination = Size::new(area.size.width, area.size.height / 3);
+    let font = largest_fitting(&DESTINATION_FONTS, &arrival.destination_name, destination);
+    let destination_name = fit_text(
+        &arrival.destination_name,
+        area.size.width as i32,
+        &[font],
+        Fit::Abbreviate,
+    );
+    draw_text(
+        display,
+        styles,
+        font,
+        destination_name
+            .lines
+            .first()
+            .map_or("", |line| line.as_str()),
+        area.top_left,
+        VerticalPosition::Top,
+        HorizontalAlignment::Left,
+    )?;
+
+    // Time to station, centred in the rest, clear of the icon each side so it stays centred
+    let top = FontMetrics::of(font).height(1) + MARGIN;
+    let time_area = Rectangle::new(
+        area.top_left + Point::new(icon_size as i32, top),
+        Size::new(
+            area.size.width.saturating_sub(2 * icon_size),
+            (area.size.height as i32 - top).max(0) as u32,
+        ),
+    );
+    let time_to_station = styles
+        .strings
+        .time_to_station(arrival.time_to_station, arrival.uncertain);
+    let font = largest_fitting(&TIME_FONTS, &time_to_station, time_area.size);
+    draw_text(
+        display,
+        styles,
+        font,
+        &time_to_station,
+        time_area.center(),
+        VerticalPosition::Center,
+        HorizontalAlignment::Center,
+    )?;
+
+    Ok(())
+}
+
+/// Draw a message in place of the next train, wrapped to fit
+fn draw_message<D>(
+    display: &mut D,
+    styles: &DisplayStyles<D::Color>,
+    area: Rectangle,
+    message: &str,
+) -> Result<(), DisplayError>
+where
+    D: DrawTarget + SmoothEdges,
+{
+    let font = &styles.header_font;
+    let fitted = fit_text(message, area.size.width as i32, &[font], Fit::Wrap);
+    let line_height = FontMetrics::of(font).line_height;
+    for (idx, line) in fitted.lines.iter().enumerate() {
+        draw_text(
+            display,
+            styles,
+            font,
+            line,
+            area.top_left + Point::new(0, line_height * idx as i32),
+            VerticalPosition::Top,
+            HorizontalAlignment::Left,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// The largest of the fonts in which the text fits the size, or else the smallest
+fn largest_fitting<'a>(fonts: &'a [FontRenderer], text: &str, size: Size) -> &'a FontRenderer {
+    fonts
+        .iter()
+        .find(|font| {
+            FontMetrics::of(font).height(1) <= size.height as i32
+                && font.text_width(text) <= size.width as i32
+        })
+        .unwrap_or(&fonts[fonts.len() - 1])
+}
+
+/// Draw a line of text in the foreground color, smoothing its edges
+fn draw_text<D>(
+    display: &mut D,
+    styles: &DisplayStyles<D::Color>,
+    font: &FontRenderer,
+    text: &str,
+    pos: Point,
+    vertical: VerticalPosition,
+    horizontal: HorizontalAlignment,
+) -> Result<(), DisplayError>
+where
+    D: DrawTarget + SmoothEdges,
+{
+    let bounds = font
+        .render_aligned(
+            text,
+            pos,
+            vertical,
+            horizontal,
+            FontColor::Transparent(styles.colors.fg),
+            display,
+        )
+        .map_err(|_| DisplayError::RenderingFailed)?;
+    if let Some(bounds) = bounds {
+        display.smooth_edges(bounds, styles.scheme);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,196 @@
+//! Display layout
+//!
+//! Splits the display into regions (header, arrivals list and footer), sized
+//! from the metrics of the fonts drawn in them rather than fixed pixel
+//! coordinates, so that the layout adapts to the rotation and size of the
+//! panel. Arrival rows are packed top down into their region, until full.
+//!
+//! The fonts and icons are themselves scaled to the size class of the panel,
+//! see `Scale`, from the 2.9" to the 7.5" panels.
+//!
+//! +-------------------------------------------------------------+
+//! | header                                                      |
+//! +-------------------------------------------------------------+
+//! | arrivals          | destination_offset                      |
+//! |                   |                                         |
+//! +-------------------------------------------------------------+
+//! | footer                                                      |
+//! +-------------------------------------------------------------+
+//!
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use u8g2_fonts::FontRenderer;
+use u8g2_fonts::types::VerticalPosition;
+
+use crate::tasks::display::draw::DisplayStyles;
+
+// Space around the edge of the display, and between columns
+pub const MARGIN: i32 = 10;
+
+// Vertical space between rows and lines
+pub const ROW_GAP: i32 = 8;
+
+// Widest expected times to station, in seconds, to size the time column
+const TIME_COLUMN_TEMPLATES: [u32; 3] = [0, 60, 88 * 60];
+
+/// Size class of the display, which the fonts and icons are scaled to
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Scale {
+    /// e.g. the 2.9" panel, 128 pixels high
+    Small,
+    /// e.g. the 3.7" and 4.2" panels, around 300 pixels high
+    Regular,
+    /// e.g. the 7.5" panel, 480 pixels high
+    Large,
+}
+
+impl Scale {
+    /// Size class of a landscape display
+    pub fn of(size: Size) -> Self {
+        match size.height {
+            0..200 => Self::Small,
+            200..=320 => Self::Regular,
+            _ => Self::Large,
+        }
+    }
+
+    /// Size of the footer status icons
+    pub const fn icon_size(self) -> u32 {
+        match self {
+            Self::Small => 18,
+            Self::Regular | Self::Large => 48,
+        }
+    }
+}
+
+/// Vertical metrics of a font
+#[derive(Copy, Clone, Debug)]
+pub struct FontMetrics {
+    // Height above the baseline
+    pub ascent: i32,
+    // Depth below the baseline
+    pub descent: i32,
+    // Distance between baselines of consecutive lines
+    pub line_height: i32,
+}
+
+impl FontMetrics {
+    pub fn of(font: &FontRenderer) -> Self {
+        Self {
+            ascent: font.get_ascent() as i32,
+            descent: (font.get_descent() as i32).abs(),
+            line_height: font.get_default_line_height() as i32,
+        }
+    }
+
+    /// Height of a block of text, from the top of the first line to the bottom of the last
+    pub fn height(&self, lines: i32) -> i32 {
+        // Including the row of the baseline, between the ascent and descent
+        self.ascent + 1 + self.descent + self.line_height * (lines - 1).max(0)
+    }
+}
+
+/// Regions of the display
+#[derive(Copy, Clone, Debug)]
+pub struct Layout {
+    pub header: Rectangle,
+    pub arrivals: Rectangle,
+    pub footer: Rectangle,
+    // Offset of the destination column from the left of an arrival row
+    pub destination_offset: i32,
+}
+
+impl Layout {
+    pub fn new<C>(bounds: Rectangle, styles: &DisplayStyles<C>) -> Self {
+        let width = bounds.size.width;
+
+        // Header of two lines, e.g. line and station, then platform
+        let header_height = MARGIN + FontMetrics::of(&styles.header_font).height(2) + ROW_GAP;
+        let header = Rectangle::new(bounds.top_left, Size::new(width, header_height as u32));
+
+        // Footer of the status icon, or the last updated time if taller
+        let footer_height = (styles.scale.icon_size() as i32)
+            .max(FontMetrics::of(&styles.tiny_font).height(1))
+            + ROW_GAP;
+        let footer = Rectangle::new(
+            bounds.top_left + Point::new(0, bounds.size.height as i32 - footer_height),
+            Size::new(width, footer_height as u32),
+        );
+
+        // Arrivals fill the remainder, inside the margins
+        let arrivals_top = header_height + ROW_GAP;
+        let arrivals = Rectangle::new(
+            bounds.top_left + Point::new(MARGIN, arrivals_top),
+            Size::new(
+                width.saturating_sub(2 * MARGIN as u32),
+                (bounds.size.height as i32 - arrivals_top - footer_height).max(0) as u32,
+            ),
+        );
+
+        // Each as approximate, in the strings of the language
+        let time_width = TIME_COLUMN_TEMPLATES
+            .iter()
+            .filter_map(|&seconds| {
+                let time = styles.strings.time_to_station(seconds, true);
+                styles
+                    .time_font
+                    .get_rendered_dimensions(time.as_str(), Point::zero(), VerticalPosition::Top)
+                    .ok()
+            })
+            .map(|dimensions| dimensions.advance.x)
+            .max()
+            .unwrap_or_default();
+
+        Self {
+            header,
+            arrivals,
+            footer,
+            destination_offset: time_width + MARGIN,
+        }
+    }
+
+    /// Everything below the header, redrawn on a partial refresh
+    pub fn body(&self) -> Rectangle {
+        let top = self.header.top_left.y + self.header.size.height as i32;
+        let bottom = self.footer.top_left.y + self.footer.size.height as i32;
+        Rectangle::new(
+            Point::new(self.header.top_left.x, top),
+            Size::new(self.header.size.width, (bottom - top).max(0) as u32),
+        )
+    }
+}
+
+/// Packs rows top down into a region
+pub struct Rows {
+    area: Rectangle,
+    cursor: i32,
+}
+
+impl Rows {
+    pub fn new(area: Rectangle) -> Self {
+        Self {
+            area,
+            cursor: area.top_left.y,
+        }
+    }
+
+    /// Width of each row
+    pub fn width(&self) -> i32 {
+        self.area.size.width as i32
+    }
+
+    /// Take the next row, if it fits in what remains of the region
+    pub fn next(&mut self, height: i32) -> Option<Rectangle> {
+        let bottom = self.area.top_left.y + self.area.size.height as i32;
+        if self.cursor + height > bottom {
+            return None;
+        }
+
+        let row = Rectangle::new(
+            Point::new(self.area.top_left.x, self.cursor),
+            Size::new(self.area.size.width, height as u32),
+        );
+        self.cursor += height + ROW_GAP;
+        Some(row)
+    }
+}
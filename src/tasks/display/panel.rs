@@ -0,0 +1,315 @@
+//! Display panel backends
+//!
+//! Wraps the `epd-waveshare` driver and display buffer of the panel model
+//! selected by cargo feature, so that the rest of the firmware is independent
+//! of the model:
+//!
+//! - `epd3in7`: Waveshare 3.7" (480x280), the default
+//! - `epd2in9`: Waveshare 2.9" V2 (296x128)
+//! - `epd4in2`: Waveshare 4.2" (400x300)
+//! - `epd7in5`: Waveshare 7.5" V2 (800x480)
+//!
+//! e.g. `cargo run --release --no-default-features --features epd4in2`
+//!
+//! The buffer is always drawn in landscape, with the layout adapting to the
+//! resolution of the panel, see `layout`.
+//!
+//! The controller may be put into deep sleep between refreshes, in which it
+//! draws next to no power whilst the panel holds the image, and is woken by
+//! the next flush. Each change of its power state is logged, with the time
+//! spent in each, to measure the saving. A controller that holds BUSY for too
+//! long is taken to have hung, and is reset by the next wake.
+//!
+use ::function_name::named;
+use defmt::{Format, info, warn};
+use embassy_time::{Delay, Instant};
+use embedded_graphics::prelude::*;
+use embedded_hal_1::digital::{InputPin, OutputPin};
+use embedded_hal_1::spi::SpiDevice;
+use epd_waveshare::color::Color;
+use epd_waveshare::prelude::{RefreshLut, WaveshareDisplay};
+
+use crate::tasks::display::gray4::SmoothEdges;
+use crate::tasks::display::pins::{BusyTimeout, take_busy_timed_out};
+use crate::tasks::display::refresh::Refresh;
+
+#[cfg(not(any(
+    feature = "epd3in7",
+    feature = "epd2in9",
+    feature = "epd4in2",
+    feature = "epd7in5"
+)))]
+compile_error!(
+    "Select a display panel with one of the epd3in7, epd2in9, epd4in2 or epd7in5 features"
+);
+
+#[cfg(any(
+    all(feature = "epd3in7", feature = "epd2in9"),
+    all(feature = "epd3in7", feature = "epd4in2"),
+    all(feature = "epd3in7", feature = "epd7in5"),
+    all(feature = "epd2in9", feature = "epd4in2"),
+    all(feature = "epd2in9", feature = "epd7in5"),
+    all(feature = "epd4in2", feature = "epd7in5"),
+))]
+compile_error!(
+    "Select only one display panel, with --no-default-features for other than the epd3in7"
+);
+
+#[cfg(feature = "epd3in7")]
+mod model {
+    use epd_waveshare::prelude::DisplayRotation;
+
+    pub use epd_waveshare::epd3in7::{Display3in7 as Buffer, EPD3in7 as Epd};
+
+    // Landscape mode, USB plug to the right
+    pub const ROTATION: DisplayRotation = DisplayRotation::Rotate270;
+    pub const QUICK_REFRESH: bool = true;
+    pub const GRAYSCALE: bool = true;
+    pub const BUSY_HIGH: bool = true;
+}
+
+#[cfg(feature = "epd2in9")]
+mod model {
+    use epd_waveshare::prelude::DisplayRotation;
+
+    use crate::tasks::display::gray4::SmoothEdges;
+
+    pub use epd_waveshare::epd2in9_v2::{Display2in9 as Buffer, Epd2in9 as Epd};
+
+    // Landscape mode, connector to the right
+    pub const ROTATION: DisplayRotation = DisplayRotation::Rotate270;
+    // The driver only records the LUT, and always refreshes fully, so partial refreshes are
+    // full refreshes
+    pub const QUICK_REFRESH: bool = false;
+    pub const GRAYSCALE: bool = false;
+    pub const BUSY_HIGH: bool = true;
+
+    impl SmoothEdges for Buffer {}
+}
+
+#[cfg(feature = "epd4in2")]
+mod model {
+    use epd_waveshare::prelude::DisplayRotation;
+
+    use crate::tasks::display::gray4::SmoothEdges;
+
+    pub use epd_waveshare::epd4in2::{Display4in2 as Buffer, Epd4in2 as Epd};
+
+    // Natively landscape
+    pub const ROTATION: DisplayRotation = DisplayRotation::Rotate0;
+    pub const QUICK_REFRESH: bool = true;
+    pub const GRAYSCALE: bool = false;
+    // BUSY is held low whilst busy
+    pub const BUSY_HIGH: bool = false;
+
+    impl SmoothEdges for Buffer {}
+}
+
+#[cfg(feature = "epd7in5")]
+mod model {
+    use epd_waveshare::prelude::DisplayRotation;
+
+    use crate::tasks::display::gray4::SmoothEdges;
+
+    pub use epd_waveshare::epd7in5_v2::{Display7in5 as Buffer, Epd7in5 as Epd};
+
+    // Natively landscape
+    pub const ROTATION: DisplayRotation = DisplayRotation::Rotate0;
+    // No quick waveform (or LUT selection), so partial refreshes are full refreshes
+    pub const QUICK_REFRESH: bool = false;
+    pub const GRAYSCALE: bool = false;
+    // BUSY is held low whilst busy
+    pub const BUSY_HIGH: bool = false;
+
+    impl SmoothEdges for Buffer {}
+}
+
+/// Whether the panel supports the grayscale renderer, see `gray4`
+pub const GRAYSCALE_SUPPORTED: bool = model::GRAYSCALE;
+
+/// Whether the panel has a quick waveform for partial refreshes, or only refreshes fully
+pub const QUICK_REFRESH_SUPPORTED: bool = model::QUICK_REFRESH;
+
+/// A display panel, and the buffer drawn on for it
+pub trait DisplayBackend<SPI, BUSY, DC, RST>: Sized {
+    type Buffer: DrawTarget<Color = Color> + SmoothEdges;
+
+    /// Initialise the panel, resetting it, and an empty buffer
+    fn init(spi: &mut SPI, busy: BUSY, dc: DC, rst: RST) -> Result<Self, PanelError>;
+
+    /// The buffer to draw on, in landscape
+    fn buffer(&mut self) -> &mut Self::Buffer;
+
+    /// The raw frame of the buffer, as sent to the panel
+    fn frame(&self) -> &[u8];
+
+    /// Send the buffer to the panel, and refresh with the waveform for the refresh, waking the
+    /// panel first if need be
+    fn flush(&mut self, spi: &mut SPI, refresh: Refresh) -> Result<(), PanelError>;
+
+    /// Put the panel into deep sleep, which keeps the image shown, unless not awake
+    fn sleep(&mut self, spi: &mut SPI) -> Result<(), PanelError>;
+
+    /// Wake the panel from deep sleep, or reset it if unresponsive, unless already awake
+    fn wake(&mut self, spi: &mut SPI) -> Result<(), PanelError>;
+
+    /// Check that a wait on BUSY outside of the driver, e.g. by the grayscale renderer, did not
+    /// time out, otherwise the panel is reset by the next wake
+    fn check_busy(&mut self) -> Result<(), PanelError>;
+}
+
+/// Power state of the panel controller
+#[derive(Copy, Clone, Debug, Format, Eq, PartialEq)]
+enum PanelState {
+    /// Powered, and ready to refresh
+    Awake,
+    /// In deep sleep, drawing next to no power
+    Asleep,
+    /// Held BUSY for too long, so to be reset
+    Unresponsive,
+}
+
+/// The panel selected by cargo feature
+pub struct Panel<SPI, BUSY, DC, RST> {
+    epd: model::Epd<SPI, BusyTimeout<BUSY>, DC, RST, Delay>,
+    buffer: model::Buffer,
+    state: PanelState,
+    // When the state last changed, and the total time spent powered and in deep sleep before
+    state_since: Instant,
+    awake_ms: u64,
+    asleep_ms: u64,
+}
+
+impl<SPI, BUSY, DC, RST> Panel<SPI, BUSY, DC, RST> {
+    /// Change the power state, logging the time spent in each
+    #[named]
+    fn set_state(&mut self, state: PanelState) {
+        let elapsed_ms = self.state_since.elapsed().as_millis();
+        match self.state {
+            PanelState::Asleep => self.asleep_ms += elapsed_ms,
+            PanelState::Awake | PanelState::Unresponsive => self.awake_ms += elapsed_ms,
+        }
+        info!(
+            "{}: Panel {} after {} ms, now {}, {} ms awake and {} ms asleep in total",
+            function_name!(),
+            self.state,
+            elapsed_ms,
+            state,
+            self.awake_ms,
+            self.asleep_ms
+        );
+        self.state = state;
+        self.state_since = Instant::now();
+    }
+
+    /// The result of a call to the driver, or a timeout if it waited on BUSY for too long
+    #[named]
+    fn checked<E>(&mut self, result: Result<(), E>) -> Result<(), PanelError> {
+        if take_busy_timed_out() {
+            warn!(
+                "{}: Timed out waiting on BUSY, the panel will be reset",
+                function_name!()
+            );
+            self.set_state(PanelState::Unresponsive);
+            return Err(PanelError::BusyTimeout);
+        }
+        result.map_err(|_| PanelError::InterfaceFailed)
+    }
+}
+
+impl<SPI, BUSY, DC, RST> DisplayBackend<SPI, BUSY, DC, RST> for Panel<SPI, BUSY, DC, RST>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+{
+    type Buffer = model::Buffer;
+
+    fn init(spi: &mut SPI, busy: BUSY, dc: DC, rst: RST) -> Result<Self, PanelError> {
+        let busy = BusyTimeout::new(busy, model::BUSY_HIGH);
+        let epd = model::Epd::new(spi, busy, dc, rst, &mut Delay, None);
+        if take_busy_timed_out() {
+            return Err(PanelError::BusyTimeout);
+        }
+        let epd = epd.map_err(|_| PanelError::InterfaceFailed)?;
+
+        let mut buffer = model::Buffer::default();
+        buffer.set_rotation(model::ROTATION);
+
+        Ok(Self {
+            epd,
+            buffer,
+            state: PanelState::Awake,
+            state_since: Instant::now(),
+            awake_ms: 0,
+            asleep_ms: 0,
+        })
+    }
+
+    fn buffer(&mut self) -> &mut Self::Buffer {
+        &mut self.buffer
+    }
+
+    fn frame(&self) -> &[u8] {
+        self.buffer.buffer()
+    }
+
+    fn flush(&mut self, spi: &mut SPI, refresh: Refresh) -> Result<(), PanelError> {
+        self.wake(spi)?;
+
+        // The quick waveform only drives the pixels that differ from the previous frame,
+        // so the whole buffer is sent, and only the changed regions visibly refresh
+        // Panels without it only have the full waveform, so it is left as is
+        if model::QUICK_REFRESH {
+            let lut = match refresh {
+                Refresh::Full => RefreshLut::Full,
+                Refresh::Partial => RefreshLut::Quick,
+            };
+            let result = self.epd.set_lut(spi, &mut Delay, Some(lut));
+            self.checked(result)?;
+        }
+
+        let result = self
+            .epd
+            .update_and_display_frame(spi, self.buffer.buffer(), &mut Delay);
+        self.checked(result)
+    }
+
+    fn sleep(&mut self, spi: &mut SPI) -> Result<(), PanelError> {
+        // An unresponsive panel is left to be reset by the next wake
+        if self.state != PanelState::Awake {
+            return Ok(());
+        }
+
+        let result = self.epd.sleep(spi, &mut Delay);
+        self.checked(result)?;
+        self.set_state(PanelState::Asleep);
+        Ok(())
+    }
+
+    fn wake(&mut self, spi: &mut SPI) -> Result<(), PanelError> {
+        if self.state == PanelState::Awake {
+            return Ok(());
+        }
+
+        // Waking resets the controller, and initialises it again
+        let result = self.epd.wake_up(spi, &mut Delay);
+        self.checked(result)?;
+        self.set_state(PanelState::Awake);
+        Ok(())
+    }
+
+    fn check_busy(&mut self) -> Result<(), PanelError> {
+        self.checked(Ok::<(), ()>(()))
+    }
+}
+
+/// Represents any error that may happen when driving the panel.
+#[derive(Copy, Clone, Debug, Format, Eq, PartialEq)]
+pub enum PanelError {
+    /// The SPI transfer or a control pin failed
+    InterfaceFailed,
+    /// The controller held BUSY for too long, so is reset by the next wake
+    BusyTimeout,
+}
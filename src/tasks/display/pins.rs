@@ -0,0 +1,122 @@
+//! Display pins
+//!
+//! The EPD driver takes ownership of its BUSY and DC pins, however the
+//! grayscale renderer (see `gray4`) must also drive the same controller.
+//! These wrap a pin, so that it can be handed to both, which is sound as they
+//! are only ever used from the display task, one after the other.
+//!
+//! The EPD driver, and the grayscale renderer, wait on BUSY without a
+//! timeout, so would hang the display task with the controller, which is
+//! avoided by `BusyTimeout`.
+//!
+use core::cell::RefCell;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use embassy_time::{Duration, Instant};
+use embedded_hal_1::digital::{ErrorType, InputPin, OutputPin};
+
+// Longest BUSY may be held, well beyond the slowest full refresh, before the controller is
+// taken to have hung
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Set when a wait on BUSY has timed out, until taken
+static BUSY_TIMED_OUT: AtomicBool = AtomicBool::new(false);
+
+pub struct SharedPin<P: 'static> {
+    pin: &'static RefCell<P>,
+}
+
+impl<P> SharedPin<P> {
+    pub fn new(pin: &'static RefCell<P>) -> Self {
+        Self { pin }
+    }
+}
+
+impl<P> Clone for SharedPin<P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<P> Copy for SharedPin<P> {}
+
+impl<P: ErrorType> ErrorType for SharedPin<P> {
+    type Error = P::Error;
+}
+
+impl<P: OutputPin> OutputPin for SharedPin<P> {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.pin.borrow_mut().set_low()
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.pin.borrow_mut().set_high()
+    }
+}
+
+impl<P: InputPin> InputPin for SharedPin<P> {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        self.pin.borrow_mut().is_high()
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        self.pin.borrow_mut().is_low()
+    }
+}
+
+/// A BUSY pin that reads as idle once held busy for too long, so that the waits of the EPD
+/// driver end, see `take_busy_timed_out`
+pub struct BusyTimeout<P> {
+    pin: P,
+    // Level of the pin whilst the controller is busy, which depends on the panel
+    busy_high: bool,
+    busy_since: Option<Instant>,
+}
+
+impl<P> BusyTimeout<P> {
+    pub fn new(pin: P, busy_high: bool) -> Self {
+        Self {
+            pin,
+            busy_high,
+            busy_since: None,
+        }
+    }
+
+    /// The level to report for the level read, reporting idle once timed out
+    fn level(&mut self, high: bool) -> bool {
+        if high != self.busy_high {
+            self.busy_since = None;
+            return high;
+        }
+
+        let busy_since = *self.busy_since.get_or_insert_with(Instant::now);
+        if busy_since.elapsed() < BUSY_TIMEOUT {
+            return high;
+        }
+
+        BUSY_TIMED_OUT.store(true, Ordering::Relaxed);
+        self.busy_since = None;
+        !high
+    }
+}
+
+impl<P: ErrorType> ErrorType for BusyTimeout<P> {
+    type Error = P::Error;
+}
+
+impl<P: InputPin> InputPin for BusyTimeout<P> {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        let high = self.pin.is_high()?;
+        Ok(self.level(high))
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        let high = self.pin.is_high()?;
+        Ok(!self.level(high))
+    }
+}
+
+/// Whether a wait on BUSY has timed out since last taken
+pub fn take_busy_timed_out() -> bool {
+    BUSY_TIMED_OUT.swap(false, Ordering::Relaxed)
+}
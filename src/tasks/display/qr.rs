@@ -0,0 +1,576 @@
+//! QR code encoding
+//!
+//! A small `no_std` QR code encoder, without allocation, for the links and
+//! reports drawn on the display, e.g. to the troubleshooting notes for an
+//! error. Only byte mode is supported, in versions 1 to 10 (up to 57x57
+//! modules), which holds up to 271 bytes at the low error correction level.
+//!
+//! The encoding follows Project Nayuki's reference QR code generator, module
+//! for module, including the choice of mask.
+//!
+//! N.B. This is included by the simulator, so must not depend on the
+//! embassy runtime or log with defmt.
+//!
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+
+// Largest version supported, which bounds the size of the buffers
+pub const MAX_VERSION: usize = 10;
+const MAX_SIZE: usize = MAX_VERSION * 4 + 17;
+// Data and error correction codewords of the largest version
+const MAX_CODEWORDS: usize = 346;
+// Error correction codewords per block, of any version supported
+const MAX_BLOCK_ECC: usize = 30;
+
+// Light modules around the code, required by readers
+pub const QUIET_ZONE: u32 = 4;
+
+// Per version (index 0 unused), from the QR code specification
+const ECC_CODEWORDS_PER_BLOCK: [[u8; MAX_VERSION + 1]; 2] = [
+    [0, 7, 10, 15, 20, 26, 18, 20, 24, 30, 18],  // Low
+    [0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26], // Medium
+];
+const NUM_ERROR_CORRECTION_BLOCKS: [[u8; MAX_VERSION + 1]; 2] = [
+    [0, 1, 1, 1, 1, 1, 2, 2, 2, 2, 4], // Low
+    [0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5], // Medium
+];
+
+// Weights of the mask penalty rules
+const PENALTY_N1: i32 = 3;
+const PENALTY_N2: i32 = 3;
+const PENALTY_N3: i32 = 40;
+const PENALTY_N4: i32 = 10;
+
+/// Error correction level, the share of the code that may be damaged and still read
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Ecc {
+    /// Around 7%
+    Low,
+    /// Around 15%
+    Medium,
+}
+
+impl Ecc {
+    fn index(self) -> usize {
+        match self {
+            Self::Low => 0,
+            Self::Medium => 1,
+        }
+    }
+
+    fn format_bits(self) -> u32 {
+        match self {
+            Self::Low => 1,
+            Self::Medium => 0,
+        }
+    }
+}
+
+/// Represents any error that may happen encoding a QR code.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum QrError {
+    /// The data does not fit in the largest version supported
+    TooLong,
+}
+
+/// A QR code, of dark and light modules
+#[derive(Clone, Debug)]
+pub struct QrCode {
+    size: usize,
+    // Each row as a bit mask, with the module at x in bit x, set if dark
+    modules: [u64; MAX_SIZE],
+    // Modules of the function patterns, which are not masked
+    functions: [u64; MAX_SIZE],
+}
+
+impl QrCode {
+    /// Encode the data in the smallest version that holds it
+    pub fn encode(data: &[u8], ecc: Ecc) -> Result<Self, QrError> {
+        let version = (1..=MAX_VERSION)
+            .find(|&version| {
+                4 + count_bits(version) + data.len() * 8 <= num_data_codewords(version, ecc) * 8
+            })
+            .ok_or(QrError::TooLong)?;
+
+        // Byte mode, the count and the data, then terminated and padded to the capacity
+        let capacity = num_data_codewords(version, ecc);
+        let mut codewords = [0u8; MAX_CODEWORDS];
+        let mut writer = BitWriter {
+            codewords: &mut codewords[..capacity],
+            len: 0,
+        };
+        writer.push(0b0100, 4);
+        writer.push(data.len() as u32, count_bits(version));
+        for &byte in data {
+            writer.push(byte.into(), 8);
+        }
+        writer.push(0, (capacity * 8 - writer.len).min(4));
+        writer.push(0, (8 - writer.len % 8) % 8);
+        for pad in [0xEC, 0x11]
+            .into_iter()
+            .cycle()
+            .take(capacity - writer.len / 8)
+        {
+            writer.push(pad, 8);
+        }
+
+        let mut qr = Self {
+            size: version * 4 + 17,
+            modules: [0; MAX_SIZE],
+            functions: [0; MAX_SIZE],
+        };
+        qr.draw_function_patterns(version, ecc);
+        let interleaved = add_ecc_and_interleave(&codewords[..capacity], version, ecc);
+        qr.draw_codewords(&interleaved[..num_raw_data_modules(version) / 8]);
+
+        // Choose the mask with the lowest penalty, each is undone by applying it again
+        let mask = (0..8)
+            .min_by_key(|&mask| {
+                qr.apply_mask(mask);
+                qr.draw_format_bits(ecc, mask);
+                let penalty = qr.penalty_score();
+                qr.apply_mask(mask);
+                penalty
+            })
+            .unwrap_or_default();
+        qr.apply_mask(mask);
+        qr.draw_format_bits(ecc, mask);
+
+        Ok(qr)
+    }
+
+    /// Whether the module is dark
+    pub fn module(&self, x: usize, y: usize) -> bool {
+        (self.modules[y] >> x) & 1 != 0
+    }
+
+    /// Width and height drawn with each module a square of pixels, including the quiet zone
+    pub fn drawn_size(&self, module_size: u32) -> u32 {
+        (self.size as u32 + 2 * QUIET_ZONE) * module_size
+    }
+
+    /// Draw with each module a square of pixels, inside the quiet zone
+    pub fn draw<D>(
+        &self,
+        display: &mut D,
+        top_left: Point,
+        module_size: u32,
+        dark: D::Color,
+        light: D::Color,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget,
+    {
+        display.fill_solid(
+            &Rectangle::new(top_left, Size::new_equal(self.drawn_size(module_size))),
+            light,
+        )?;
+
+        let origin = top_left + Point::new_equal((QUIET_ZONE * module_size) as i32);
+        for y in 0..self.size {
+            for x in (0..self.size).filter(|&x| self.module(x, y)) {
+                let pos = Point::new(x as i32, y as i32) * module_size as i32;
+                display.fill_solid(
+                    &Rectangle::new(origin + pos, Size::new_equal(module_size)),
+                    dark,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y] = (self.modules[y] & !(1 << x)) | (u64::from(dark) << x);
+        self.functions[y] |= 1 << x;
+    }
+
+    fn is_function(&self, x: usize, y: usize) -> bool {
+        (self.functions[y] >> x) & 1 != 0
+    }
+
+    /// Draw the timing, finder and alignment patterns, and the format and version
+    fn draw_function_patterns(&mut self, version: usize, ecc: Ecc) {
+        let size = self.size;
+
+        for i in 0..size {
+            self.set_function(6, i, i % 2 == 0);
+            self.set_function(i, 6, i % 2 == 0);
+        }
+
+        // Finders in three corners, with their separators
+        for (x, y) in [(3, 3), (size - 4, 3), (3, size - 4)] {
+            for dy in -4..=4_i32 {
+                for dx in -4..=4_i32 {
+                    let (Some(xx), Some(yy)) = (
+                        x.checked_add_signed(dx as isize),
+                        y.checked_add_signed(dy as isize),
+                    ) else {
+                        continue;
+                    };
+                    if xx < size && yy < size {
+                        let distance = dx.abs().max(dy.abs());
+                        self.set_function(xx, yy, distance != 2 && distance != 4);
+                    }
+                }
+            }
+        }
+
+        // Alignment patterns, except where they would overlap the finders
+        let (positions, count) = alignment_pattern_positions(version, size);
+        for i in 0..count {
+            for j in 0..count {
+                let last = count - 1;
+                if (i, j) == (0, 0) || (i, j) == (0, last) || (i, j) == (last, 0) {
+                    continue;
+                }
+                for dy in -2..=2_i32 {
+                    for dx in -2..=2_i32 {
+                        self.set_function(
+                            positions[i].saturating_add_signed(dx as isize),
+                            positions[j].saturating_add_signed(dy as isize),
+                            dx.abs().max(dy.abs()) != 1,
+                        );
+                    }
+                }
+            }
+        }
+
+        // Reserve the format bits, drawn again once the mask is chosen
+        self.draw_format_bits(ecc, 0);
+        self.draw_version(version);
+    }
+
+    fn draw_format_bits(&mut self, ecc: Ecc, mask: u32) {
+        let data = ecc.format_bits() << 3 | mask;
+        let mut remainder = data;
+        for _ in 0..10 {
+            remainder = (remainder << 1) ^ ((remainder >> 9) * 0x537);
+        }
+        let bits = (data << 10 | remainder) ^ 0x5412;
+        let bit = |i: usize| (bits >> i) & 1 != 0;
+
+        // Around the top left finder
+        for i in 0..6 {
+            self.set_function(8, i, bit(i));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i));
+        }
+
+        // Split between the other two finders
+        let size = self.size;
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(i));
+        }
+        self.set_function(8, size - 8, true);
+    }
+
+    fn draw_version(&mut self, version: usize) {
+        if version < 7 {
+            return;
+        }
+
+        let data = version as u32;
+        let mut remainder = data;
+        for _ in 0..12 {
+            remainder = (remainder << 1) ^ ((remainder >> 11) * 0x1F25);
+        }
+        let bits = data << 12 | remainder;
+
+        for i in 0..18 {
+            let dark = (bits >> i) & 1 != 0;
+            let a = self.size - 11 + i % 3;
+            let b = i / 3;
+            self.set_function(a, b, dark);
+            self.set_function(b, a, dark);
+        }
+    }
+
+    /// Place the codewords in the zigzag scan of the data modules
+    fn draw_codewords(&mut self, codewords: &[u8]) {
+        let mut i = 0;
+        let mut right = self.size - 1;
+        while right >= 1 {
+            if right == 6 {
+                right = 5;
+            }
+            for vert in 0..self.size {
+                for j in 0..2 {
+                    let x = right - j;
+                    let upward = (right + 1) & 2 == 0;
+                    let y = if upward { self.size - 1 - vert } else { vert };
+                    if !self.is_function(x, y) && i < codewords.len() * 8 {
+                        let dark = (codewords[i >> 3] >> (7 - (i & 7))) & 1 != 0;
+                        self.modules[y] |= u64::from(dark) << x;
+                        i += 1;
+                    }
+                }
+            }
+            if right < 2 {
+                break;
+            }
+            right -= 2;
+        }
+    }
+
+    fn apply_mask(&mut self, mask: u32) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let invert = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                if invert && !self.is_function(x, y) {
+                    self.modules[y] ^= 1 << x;
+                }
+            }
+        }
+    }
+
+    /// Penalty of the masked modules, for patterns that are hard to read
+    fn penalty_score(&self) -> i32 {
+        let size = self.size;
+        let mut result = 0;
+
+        // Runs of the same colour, and finder like patterns, in rows then columns
+        for transpose in [false, true] {
+            for a in 0..size {
+                let module = |b| match transpose {
+                    false => self.module(b, a),
+                    true => self.module(a, b),
+                };
+                let mut run_color = false;
+                let mut run = 0;
+                let mut history = FinderPenalty::new(size as i32);
+                for b in 0..size {
+                    if module(b) == run_color {
+                        run += 1;
+                        if run == 5 {
+                            result += PENALTY_N1;
+                        } else if run > 5 {
+                            result += 1;
+                        }
+                    } else {
+                        history.add_history(run);
+                        if !run_color {
+                            result += history.count_patterns() * PENALTY_N3;
+                        }
+                        run_color = module(b);
+                        run = 1;
+                    }
+                }
+                result += history.terminate_and_count(run_color, run) * PENALTY_N3;
+            }
+        }
+
+        // 2x2 blocks of the same colour
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let color = self.module(x, y);
+                if color == self.module(x + 1, y)
+                    && color == self.module(x, y + 1)
+                    && color == self.module(x + 1, y + 1)
+                {
+                    result += PENALTY_N2;
+                }
+            }
+        }
+
+        // Balance of dark and light modules
+        let dark: i32 = self.modules[..size]
+            .iter()
+            .map(|row| row.count_ones() as i32)
+            .sum();
+        let total = (size * size) as i32;
+        let k = ((dark * 20 - total * 10).abs() + total - 1) / total - 1;
+        result + k * PENALTY_N4
+    }
+}
+
+/// Bits appended to the codewords, most significant first
+struct BitWriter<'a> {
+    codewords: &'a mut [u8],
+    len: usize,
+}
+
+impl BitWriter<'_> {
+    fn push(&mut self, value: u32, bits: usize) {
+        for i in (0..bits).rev() {
+            if (value >> i) & 1 != 0 {
+                self.codewords[self.len / 8] |= 0x80 >> (self.len % 8);
+            }
+            self.len += 1;
+        }
+    }
+}
+
+/// Run history of a row or column, to find patterns that look like a finder
+struct FinderPenalty {
+    size: i32,
+    history: [i32; 7],
+}
+
+impl FinderPenalty {
+    fn new(size: i32) -> Self {
+        Self {
+            size,
+            history: [0; 7],
+        }
+    }
+
+    /// Push the run to the front, counting the light border for the first
+    fn add_history(&mut self, mut run: i32) {
+        if self.history[0] == 0 {
+            run += self.size;
+        }
+        self.history.copy_within(0..6, 1);
+        self.history[0] = run;
+    }
+
+    /// Finder like patterns ending at the light run just added
+    fn count_patterns(&self) -> i32 {
+        let h = &self.history;
+        let n = h[1];
+        let core = n > 0 && h[2] == n && h[3] == n * 3 && h[4] == n && h[5] == n;
+        i32::from(core && h[0] >= n * 4 && h[6] >= n)
+            + i32::from(core && h[6] >= n * 4 && h[0] >= n)
+    }
+
+    /// Terminate the row or column, with the light border
+    fn terminate_and_count(mut self, run_color: bool, mut run: i32) -> i32 {
+        if run_color {
+            self.add_history(run);
+            run = 0;
+        }
+        self.add_history(run + self.size);
+        self.count_patterns()
+    }
+}
+
+/// Bits of the character count, in byte mode
+fn count_bits(version: usize) -> usize {
+    if version <= 9 { 8 } else { 16 }
+}
+
+/// Modules for data and error correction, after the function patterns
+fn num_raw_data_modules(version: usize) -> usize {
+    let mut result = (16 * version + 128) * version + 64;
+    if version >= 2 {
+        let num_align = version / 7 + 2;
+        result -= (25 * num_align - 10) * num_align - 55;
+        if version >= 7 {
+            result -= 36;
+        }
+    }
+    result
+}
+
+fn num_data_codewords(version: usize, ecc: Ecc) -> usize {
+    num_raw_data_modules(version) / 8
+        - ECC_CODEWORDS_PER_BLOCK[ecc.index()][version] as usize
+            * NUM_ERROR_CORRECTION_BLOCKS[ecc.index()][version] as usize
+}
+
+/// Centres of the alignment patterns, along each axis
+fn alignment_pattern_positions(version: usize, size: usize) -> ([usize; 3], usize) {
+    let mut positions = [0; 3];
+    if version == 1 {
+        return (positions, 0);
+    }
+
+    let count = version / 7 + 2;
+    let step = (version * 4 + count * 2 + 1) / (count * 2 - 2) * 2;
+    positions[0] = 6;
+    for (i, position) in positions.iter_mut().enumerate().take(count).skip(1) {
+        *position = size - 7 - (count - 1 - i) * step;
+    }
+    (positions, count)
+}
+
+/// Split into blocks, append the error correction to each, then interleave the blocks
+fn add_ecc_and_interleave(data: &[u8], version: usize, ecc: Ecc) -> [u8; MAX_CODEWORDS] {
+    let num_blocks = NUM_ERROR_CORRECTION_BLOCKS[ecc.index()][version] as usize;
+    let block_ecc_len = ECC_CODEWORDS_PER_BLOCK[ecc.index()][version] as usize;
+    let raw_codewords = num_raw_data_modules(version) / 8;
+    let num_short_blocks = num_blocks - raw_codewords % num_blocks;
+    let short_data_len = raw_codewords / num_blocks - block_ecc_len;
+    // Data codewords of every block before the extra one of the long blocks
+    let data_end = short_data_len * num_blocks + (num_blocks - num_short_blocks);
+
+    let divisor = reed_solomon_divisor(block_ecc_len);
+    let mut result = [0u8; MAX_CODEWORDS];
+    let mut offset = 0;
+    for block in 0..num_blocks {
+        let data_len = short_data_len + usize::from(block >= num_short_blocks);
+        let block_data = &data[offset..offset + data_len];
+        offset += data_len;
+
+        for (i, &byte) in block_data.iter().enumerate() {
+            result[match i < short_data_len {
+                true => i * num_blocks + block,
+                false => short_data_len * num_blocks + block - num_short_blocks,
+            }] = byte;
+        }
+
+        let remainder = reed_solomon_remainder(block_data, &divisor[..block_ecc_len]);
+        for (i, &byte) in remainder[..block_ecc_len].iter().enumerate() {
+            result[data_end + i * num_blocks + block] = byte;
+        }
+    }
+    result
+}
+
+/// Reed-Solomon generator polynomial of the degree, highest power first, without the leading 1
+fn reed_solomon_divisor(degree: usize) -> [u8; MAX_BLOCK_ECC] {
+    let mut result = [0u8; MAX_BLOCK_ECC];
+    result[degree - 1] = 1;
+
+    let mut root = 1;
+    for _ in 0..degree {
+        for j in 0..degree {
+            result[j] = reed_solomon_multiply(result[j], root);
+            if j + 1 < degree {
+                result[j] ^= result[j + 1];
+            }
+        }
+        root = reed_solomon_multiply(root, 0x02);
+    }
+    result
+}
+
+fn reed_solomon_remainder(data: &[u8], divisor: &[u8]) -> [u8; MAX_BLOCK_ECC] {
+    let degree = divisor.len();
+    let mut result = [0u8; MAX_BLOCK_ECC];
+    for &byte in data {
+        let factor = byte ^ result[0];
+        result.copy_within(1..degree, 0);
+        result[degree - 1] = 0;
+        for (x, &y) in result[..degree].iter_mut().zip(divisor) {
+            *x ^= reed_solomon_multiply(y, factor);
+        }
+    }
+    result
+}
+
+/// Multiply in GF(2^8), modulo the QR code polynomial 0x11D
+fn reed_solomon_multiply(x: u8, y: u8) -> u8 {
+    let mut z: u8 = 0;
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x1D);
+        z ^= ((y >> i) & 1) * x;
+    }
+    z
+}
@@ -0,0 +1,130 @@
+//! Display refresh planning
+//!
+//! A full refresh of the 3.7" panel flashes for around 3 seconds, whereas a
+//! partial (quick) refresh only drives the changed pixels, in well under a
+//! second, but leaves ghosting that builds up over successive refreshes.
+//!
+//! This decides which to use for each update: a partial refresh when only the
+//! arrivals have changed (e.g. a countdown tick), or a full refresh when the
+//! layout has changed, or after a configured number of partial refreshes, to
+//! clear the ghosting.
+//!
+//! Either is skipped when the drawn frame is the same as the one last flushed
+//! (e.g. the TFL API returned the same predictions within the minute), which
+//! only wastes power and wears the panel. Frames are compared by hash, rather
+//! than keeping a copy of the last.
+//!
+use defmt::Format;
+
+use crate::config::DisplayConfig;
+use crate::models::update::Update;
+
+#[derive(Copy, Clone, Debug, Format, Eq, PartialEq)]
+pub enum Refresh {
+    /// Redraw and refresh the whole display
+    Full,
+    /// Redraw and refresh only the arrivals and footer
+    Partial,
+}
+
+/// Number of refreshes performed and skipped, for diagnostics
+#[derive(Copy, Clone, Debug, Format, Eq, PartialEq)]
+pub struct RefreshCounts {
+    pub full: u32,
+    pub partial: u32,
+    pub skipped: u32,
+}
+
+pub struct RefreshPlanner {
+    config: DisplayConfig,
+    partials_since_full: u32,
+    previous: Option<Update>,
+    // Hash of the frame last flushed to the panel
+    flushed: Option<u64>,
+    counts: RefreshCounts,
+}
+
+impl RefreshPlanner {
+    pub const fn new(config: DisplayConfig) -> Self {
+        Self {
+            config,
+            partials_since_full: 0,
+            previous: None,
+            flushed: None,
+            counts: RefreshCounts {
+                full: 0,
+                partial: 0,
+                skipped: 0,
+            },
+        }
+    }
+
+    /// Decide how to refresh for the update, remembering it for the next
+    pub fn plan(&mut self, update: &Update) -> Refresh {
+        let refresh = match &self.previous {
+            Some(previous)
+                if !layout_changed(previous, update)
+                    && self.partials_since_full < self.config.full_refresh_interval =>
+            {
+                Refresh::Partial
+            }
+            _ => Refresh::Full,
+        };
+
+        self.previous = Some(update.clone());
+
+        refresh
+    }
+
+    /// Whether the drawn frame differs from that last flushed, so needs the planned refresh,
+    /// counting the refresh as performed, or else skipped
+    pub fn needs_refresh(&mut self, refresh: Refresh, frame: &[u8]) -> bool {
+        let hash = frame_hash(frame);
+        if self.flushed == Some(hash) {
+            // A skipped full refresh is left due, to clear the ghosting with the next
+            self.counts.skipped += 1;
+            return false;
+        }
+
+        match refresh {
+            Refresh::Full => {
+                self.partials_since_full = 0;
+                self.counts.full += 1;
+            }
+            Refresh::Partial => {
+                self.partials_since_full += 1;
+                self.counts.partial += 1;
+            }
+        }
+        self.flushed = Some(hash);
+
+        true
+    }
+
+    /// Number of refreshes performed and skipped, since starting
+    pub fn counts(&self) -> RefreshCounts {
+        self.counts
+    }
+
+    /// Forget the displayed update, after the display has been drawn over (e.g. cleared for sleep)
+    pub fn invalidate(&mut self) {
+        self.previous = None;
+        self.flushed = None;
+    }
+}
+
+/// 64-bit FNV-1a hash of the frame, which is fast, and sensitive to any single pixel changing
+fn frame_hash(frame: &[u8]) -> u64 {
+    frame.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Whether anything but the arrivals has changed between updates
+fn layout_changed(previous: &Update, update: &Update) -> bool {
+    previous.line_name != update.line_name
+        || previous.station_name != update.station_name
+        || previous.platform_name != update.platform_name
+        || previous.line_status != update.line_status
+        || previous.fault != update.fault
+}
@@ -0,0 +1,326 @@
+//! UI strings
+//!
+//! Every string drawn on the screens, in the language selected by cargo
+//! feature:
+//!
+//! - British English, the default
+//! - `lang-fr`: French
+//!
+//! e.g. `cargo run --release --features lang-fr`
+//!
+//! Any string may be replaced per deployment by its key, the name of its
+//! field, with `STRING_OVERRIDES`, e.g. `("due", "Due")` in place of "<1 min".
+//! Templates take their values in place of each `{}`, in order.
+//!
+//! N.B. The line status is matched against the TFL API in English, so only
+//! the text drawn for it is translated, see `good_service`.
+//!
+use core::fmt::{Display, Write as _};
+use heapless::String;
+
+use crate::config::StringsConfig;
+
+#[cfg(not(feature = "lang-fr"))]
+const DEFAULT: Strings = Strings::EN_GB;
+
+#[cfg(feature = "lang-fr")]
+const DEFAULT: Strings = Strings::FR;
+
+/// Strings drawn on the screens
+#[derive(Copy, Clone, Debug)]
+pub struct Strings {
+    // Boot checklist
+    pub starting_up: &'static str,
+    pub boot_wifi: &'static str,
+    pub boot_address: &'static str,
+    pub boot_time: &'static str,
+    pub boot_tfl: &'static str,
+
+    // System state screens, each a title and explanation
+    pub starting: &'static str,
+    pub starting_detail: &'static str,
+    pub joining_wifi: &'static str,
+    pub joining_wifi_detail: &'static str,
+    pub wifi_failed: &'static str,
+    pub wifi_failed_detail: &'static str,
+    pub no_ip: &'static str,
+    pub no_ip_detail: &'static str,
+    pub dns_failed: &'static str,
+    pub dns_failed_detail: &'static str,
+    pub tls_failed: &'static str,
+    pub tls_failed_detail: &'static str,
+    pub auth_failed: &'static str,
+    pub auth_failed_detail: &'static str,
+    pub request_failed: &'static str,
+    pub request_failed_detail: &'static str,
+    pub no_predictions: &'static str,
+    pub no_predictions_detail: &'static str,
+    pub ready: &'static str,
+    pub ready_detail: &'static str,
+    // Template of the code, e.g. the HTTP status
+    pub error_code: &'static str,
+
+    // Sleep screen
+    // Template of the time the board resumes
+    pub back_at: &'static str,
+    // Template of the weekday, day and month names
+    pub date: &'static str,
+    pub weekdays: [&'static str; 7],
+    pub months: [&'static str; 12],
+    // Template of the line status
+    pub line_status: &'static str,
+    pub planned_closures: &'static str,
+
+    // Arrivals board
+    // Template of the line and station names
+    pub header: &'static str,
+    // Times to station, under one and two minutes, then the template of the minutes
+    pub due: &'static str,
+    pub under_two_mins: &'static str,
+    pub mins: &'static str,
+    // The line status "Good Service", as given by the TFL API
+    pub good_service: &'static str,
+    // Template of the schema version found, and that expected
+    pub schema_mismatch: &'static str,
+
+    // Panic screen, drawn in an ASCII only font
+    pub panic_title: &'static str,
+}
+
+impl Strings {
+    /// British English
+    pub const EN_GB: Self = Self {
+        starting_up: "Starting up",
+        boot_wifi: "Wi-Fi",
+        boot_address: "IP",
+        boot_time: "Time",
+        boot_tfl: "TfL",
+
+        starting: "Starting",
+        starting_detail: "Connecting to the TfL API",
+        joining_wifi: "Joining Wi-Fi",
+        joining_wifi_detail: "Connecting to the Wi-Fi network",
+        wifi_failed: "Cannot join Wi-Fi",
+        wifi_failed_detail: "Check the network name and password, which can be set over USB",
+        no_ip: "No IP address",
+        no_ip_detail: "Joined the Wi-Fi network, but no address was given by DHCP",
+        dns_failed: "Cannot find the TfL API",
+        dns_failed_detail: "The API host name could not be looked up (DNS)",
+        tls_failed: "Secure connection failed",
+        tls_failed_detail: "The TLS connection to the API host could not be made",
+        auth_failed: "API key rejected",
+        auth_failed_detail: "Check the TfL API key, which can be set over USB",
+        request_failed: "TfL API not responding",
+        request_failed_detail: "Requests for arrivals keep failing, retrying every cycle",
+        no_predictions: "No trains predicted",
+        no_predictions_detail: "TfL has no arrivals for this platform at the moment",
+        ready: "Ready",
+        ready_detail: "Waiting for arrivals",
+        error_code: "Error code: {}",
+
+        back_at: "Back at {}",
+        date: "{} {} {}",
+        weekdays: [
+            "Sunday",
+            "Monday",
+            "Tuesday",
+            "Wednesday",
+            "Thursday",
+            "Friday",
+            "Saturday",
+        ],
+        months: [
+            "January",
+            "February",
+            "March",
+            "April",
+            "May",
+            "June",
+            "July",
+            "August",
+            "September",
+            "October",
+            "November",
+            "December",
+        ],
+        line_status: "Line status: {}",
+        planned_closures: "Planned closures this weekend:",
+
+        header: "{} Line - {}",
+        due: "<1 min",
+        under_two_mins: "<2 mins",
+        mins: "{} mins",
+        good_service: "Good Service",
+        schema_mismatch: "Proxy/firmware mismatch\nSchema v{} - expected v{}",
+
+        panic_title: "SYSTEM PANIC",
+    };
+
+    /// French
+    pub const FR: Self = Self {
+        starting_up: "Démarrage",
+        boot_wifi: "Wi-Fi",
+        boot_address: "IP",
+        boot_time: "Heure",
+        boot_tfl: "TfL",
+
+        starting: "Démarrage",
+        starting_detail: "Connexion à l'API TfL",
+        joining_wifi: "Connexion au Wi-Fi",
+        joining_wifi_detail: "Connexion au réseau Wi-Fi",
+        wifi_failed: "Wi-Fi inaccessible",
+        wifi_failed_detail: "Vérifiez le nom et le mot de passe du réseau, modifiables par USB",
+        no_ip: "Pas d'adresse IP",
+        no_ip_detail: "Connecté au réseau Wi-Fi, mais aucune adresse donnée par DHCP",
+        dns_failed: "API TfL introuvable",
+        dns_failed_detail: "Le nom d'hôte de l'API n'a pas pu être résolu (DNS)",
+        tls_failed: "Échec de la connexion sécurisée",
+        tls_failed_detail: "La connexion TLS à l'API n'a pas pu être établie",
+        auth_failed: "Clé d'API refusée",
+        auth_failed_detail: "Vérifiez la clé d'API TfL, modifiable par USB",
+        request_failed: "L'API TfL ne répond pas",
+        request_failed_detail: "Les requêtes d'arrivées échouent, nouvel essai à chaque cycle",
+        no_predictions: "Aucun train prévu",
+        no_predictions_detail: "TfL n'annonce aucune arrivée à ce quai pour le moment",
+        ready: "Prêt",
+        ready_detail: "En attente des arrivées",
+        error_code: "Code d'erreur : {}",
+
+        back_at: "Retour à {}",
+        date: "{} {} {}",
+        weekdays: [
+            "dimanche", "lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi",
+        ],
+        months: [
+            "janvier",
+            "février",
+            "mars",
+            "avril",
+            "mai",
+            "juin",
+            "juillet",
+            "août",
+            "septembre",
+            "octobre",
+            "novembre",
+            "décembre",
+        ],
+        line_status: "État de la ligne : {}",
+        planned_closures: "Fermetures prévues ce week-end :",
+
+        header: "Ligne {} - {}",
+        due: "<1 min",
+        under_two_mins: "<2 min",
+        mins: "{} min",
+        good_service: "Service normal",
+        schema_mismatch: "Proxy/firmware incompatibles\nSchéma v{} - v{} attendu",
+
+        panic_title: "ERREUR SYSTEME",
+    };
+
+    /// The strings of the language selected by cargo feature, with the overrides applied
+    pub fn new(config: StringsConfig) -> Self {
+        let mut strings = DEFAULT;
+        for &(key, value) in config.overrides {
+            // Unknown keys are ignored, so that overrides outlive renamed strings
+            if let Some(string) = strings.get_mut(key) {
+                *string = value;
+            }
+        }
+        strings
+    }
+
+    /// The string of the key, the name of its field
+    fn get_mut(&mut self, key: &str) -> Option<&mut &'static str> {
+        let string = match key {
+            "starting_up" => &mut self.starting_up,
+            "boot_wifi" => &mut self.boot_wifi,
+            "boot_address" => &mut self.boot_address,
+            "boot_time" => &mut self.boot_time,
+            "boot_tfl" => &mut self.boot_tfl,
+            "starting" => &mut self.starting,
+            "starting_detail" => &mut self.starting_detail,
+            "joining_wifi" => &mut self.joining_wifi,
+            "joining_wifi_detail" => &mut self.joining_wifi_detail,
+            "wifi_failed" => &mut self.wifi_failed,
+            "wifi_failed_detail" => &mut self.wifi_failed_detail,
+            "no_ip" => &mut self.no_ip,
+            "no_ip_detail" => &mut self.no_ip_detail,
+            "dns_failed" => &mut self.dns_failed,
+            "dns_failed_detail" => &mut self.dns_failed_detail,
+            "tls_failed" => &mut self.tls_failed,
+            "tls_failed_detail" => &mut self.tls_failed_detail,
+            "auth_failed" => &mut self.auth_failed,
+            "auth_failed_detail" => &mut self.auth_failed_detail,
+            "request_failed" => &mut self.request_failed,
+            "request_failed_detail" => &mut self.request_failed_detail,
+            "no_predictions" => &mut self.no_predictions,
+            "no_predictions_detail" => &mut self.no_predictions_detail,
+            "ready" => &mut self.ready,
+            "ready_detail" => &mut self.ready_detail,
+            "error_code" => &mut self.error_code,
+            "back_at" => &mut self.back_at,
+            "date" => &mut self.date,
+            "line_status" => &mut self.line_status,
+            "planned_closures" => &mut self.planned_closures,
+            "header" => &mut self.header,
+            "due" => &mut self.due,
+            "under_two_mins" => &mut self.under_two_mins,
+            "mins" => &mut self.mins,
+            "good_service" => &mut self.good_service,
+            "schema_mismatch" => &mut self.schema_mismatch,
+            "panic_title" => &mut self.panic_title,
+            _ => return None,
+        };
+        Some(string)
+    }
+
+    /// The date, from the month (from 1), day, and weekday (from Sunday)
+    pub fn format_date(&self, month: u32, day: u32, weekday: u32) -> String<48> {
+        let weekday = self.weekdays.get(weekday as usize).copied().unwrap_or("");
+        let month = self
+            .months
+            .get((month as usize).wrapping_sub(1))
+            .copied()
+            .unwrap_or("");
+        fill(self.date, &[&weekday, &day, &month])
+    }
+
+    /// Time to station of an arrival, marked as approximate if uncertain, e.g. "~7 mins"
+    pub fn time_to_station(&self, seconds: u32, uncertain: bool) -> String<24> {
+        let mut time = String::new();
+        if uncertain {
+            let _ = write!(&mut time, "~");
+        }
+        let minutes = seconds / 60;
+        let _ = match minutes {
+            0 => write!(&mut time, "{}", self.due),
+            1 => write!(&mut time, "{}", self.under_two_mins),
+            _ => write!(&mut time, "{}", fill::<16>(self.mins, &[&minutes])),
+        };
+        time
+    }
+
+    /// The line status as drawn, translated where known
+    pub fn line_status_text<'a>(&self, line_status: &'a str) -> &'a str {
+        match line_status {
+            "Good Service" => self.good_service,
+            status => status,
+        }
+    }
+}
+
+/// Fill each `{}` of the template with the next of the values
+pub fn fill<const N: usize>(template: &str, values: &[&dyn Display]) -> String<N> {
+    let mut filled = String::new();
+    let mut values = values.iter();
+    for (idx, part) in template.split("{}").enumerate() {
+        if idx > 0
+            && let Some(value) = values.next()
+        {
+            let _ = write!(&mut filled, "{}", value);
+        }
+        let _ = write!(&mut filled, "{}", part);
+    }
+    filled
+}
@@ -0,0 +1,56 @@
+//! Typeface of the text
+//!
+//! The fonts of the header, destination names and other text, by weight and
+//! size. These are the stock u8g2 Helvetica and Logisoso fonts, or with the
+//! `typeface` feature, tables generated from a typeface of your choice, e.g.
+//! a Johnston-like sans, by the `fontgen` crate:
+//!
+//! cd fontgen
+//! cargo run --release -- --regular <regular.ttf> --bold <bold.ttf>
+//!
+//! which writes each table to `typeface/`, subset to the glyphs drawn. Those
+//! checked in are of DejaVu Sans Condensed, see `typeface/LICENSE-DejaVu`. The
+//! times and the splash keep the Logisoso numerals either way.
+//!
+//! e.g. `cargo run --release --features typeface`
+//!
+
+#[cfg(not(feature = "typeface"))]
+pub use u8g2_fonts::fonts::{
+    u8g2_font_helvB08_tf as Bold8, u8g2_font_helvB10_tf as Bold10, u8g2_font_helvB14_tf as Bold14,
+    u8g2_font_helvB18_tf as Bold18, u8g2_font_helvB24_tf as Bold24,
+    u8g2_font_helvR08_tf as Regular8, u8g2_font_helvR10_tf as Regular10,
+    u8g2_font_helvR14_tf as Regular14, u8g2_font_helvR24_tf as Regular24,
+    u8g2_font_logisoso16_tf as Display16, u8g2_font_logisoso24_tf as Display24,
+    u8g2_font_logisoso38_tf as Display38,
+};
+
+/// Fonts of the tables generated by `fontgen`, named as its `TYPEFACE`
+#[cfg(feature = "typeface")]
+macro_rules! typeface {
+    ($($font:ident => $table:literal),* $(,)?) => {
+        $(
+            pub struct $font;
+
+            impl u8g2_fonts::Font for $font {
+                const DATA: &'static [u8] = include_bytes!(concat!("typeface/", $table, ".u8g2"));
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "typeface")]
+typeface! {
+    Bold8 => "bold_8",
+    Bold10 => "bold_10",
+    Bold14 => "bold_14",
+    Bold18 => "bold_18",
+    Bold24 => "bold_24",
+    Regular8 => "regular_8",
+    Regular10 => "regular_10",
+    Regular14 => "regular_14",
+    Regular24 => "regular_24",
+    Display16 => "display_16",
+    Display24 => "display_24",
+    Display38 => "display_38",
+}
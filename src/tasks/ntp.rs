@@ -21,6 +21,8 @@ use sntpc::{NtpContext, get_time};
 use sntpc_net_embassy::UdpSocketWrapper;
 use sntpc_time_embassy::EmbassyTimestampGenerator;
 
+use crate::system;
+
 // NTP server to get time information from
 const NTP_SERVER: &str = "pool.ntp.org";
 
@@ -62,16 +64,15 @@ impl WallClock {
         Some(self.base_unix_time + elapsed_secs)
     }
 
+    /// Today's date in London, as (month, day, weekday from Sunday), to be named by the
+    /// display in its language
+    pub fn current_london_date(&self) -> Option<(u32, u32, u32)> {
+        let (_, month, day, weekday) = unix_to_london_date(self.current_unix()?);
+        Some((month, day, weekday))
+    }
+
     pub fn current_london(&self) -> Option<String<32>> {
-        match self.current_unix() {
-            Some(t) => {
-                let (hour, min, sec) = unix_to_london_time(t);
-                let mut formatted_time = String::<32>::new();
-                let _ = core::write!(&mut formatted_time, "{:02}:{:02}:{:02}", hour, min, sec);
-                Some(formatted_time)
-            }
-            None => None,
-        }
+        self.current_unix().map(format_london_time)
     }
 }
 
@@ -143,6 +144,7 @@ pub async fn ntp_task(stack: Stack<'static>) {
                     let mut clock = cell.borrow_mut();
                     clock.update(fetched_unix_time);
                 });
+                system::update_boot_progress(|progress| progress.time = true);
             }
             Err(_) => {
                 error!("{}: NTP request failed", function_name!());
@@ -186,6 +188,55 @@ pub fn unix_to_london_time(unix_sec: u64) -> (u32, u32, u32) {
     (local_hour, minute, second)
 }
 
+/// Formats a Unix timestamp (seconds) as the London local time, "HH:MM:SS"
+pub fn format_london_time(unix_sec: u64) -> String<32> {
+    let (hour, min, sec) = unix_to_london_time(unix_sec);
+    let mut formatted_time = String::<32>::new();
+    let _ = core::write!(&mut formatted_time, "{:02}:{:02}:{:02}", hour, min, sec);
+    formatted_time
+}
+
+/// Converts a Unix timestamp (seconds) into the London local date (year, month, day, weekday).
+/// The weekday is 0 for Sunday, 1 for Monday...
+pub fn unix_to_london_date(unix_sec: u64) -> (i32, u32, u32, u32) {
+    civil_from_days(london_days(unix_sec))
+}
+
+/// The London local dates (year, month, day) of the Saturday and Sunday of this weekend,
+/// or the next if it is a weekday.
+pub fn london_weekend(unix_sec: u64) -> ((i32, u32, u32), (i32, u32, u32)) {
+    let today = london_days(unix_sec);
+    let (_, _, _, weekday) = civil_from_days(today);
+
+    // Only Sunday remains, if it is already the weekend
+    let saturday = match weekday {
+        0 => today - 1,
+        weekday => today + (6 - weekday) as i64,
+    };
+
+    let date = |days: i64| {
+        let (year, month, day, _) = civil_from_days(days);
+        (year, month, day)
+    };
+    (date(saturday.max(today)), date(saturday + 1))
+}
+
+/// Days since the Unix epoch, at London local time
+fn london_days(unix_sec: u64) -> i64 {
+    const SECS_PER_HOUR: u64 = 3600;
+    const SECS_PER_DAY: u64 = 86400;
+
+    let (year, month, day, weekday) = civil_from_days((unix_sec / SECS_PER_DAY) as i64);
+    let hour = ((unix_sec % SECS_PER_DAY) / SECS_PER_HOUR) as u32;
+    let offset = if is_uk_dst_raw(year, month, day, weekday, hour) {
+        SECS_PER_HOUR
+    } else {
+        0
+    };
+
+    ((unix_sec + offset) / SECS_PER_DAY) as i64
+}
+
 /// Helper: Check UK DST boundaries dynamically
 fn is_uk_dst_raw(year: i32, month: u32, day: u32, _weekday: u32, hour_utc: u32) -> bool {
     // March (3) to October (10)
@@ -12,32 +12,53 @@
 //! Buffer sizes are carefully selected to support the Pimoroni Pico Plus 2W.
 //!  
 use ::function_name::named;
-use core::fmt::Write;
+use core::fmt::Write as _;
 use defmt::{debug, error, info, warn};
 use embassy_net::Stack;
 use embassy_net::dns::DnsSocket;
 use embassy_net::tcp::client::{TcpClient, TcpClientState};
 use embassy_rp::clocks::RoscRng;
 use embassy_time::Timer;
-use embassy_time::{Duration, with_timeout};
-use heapless::String;
-use heapless::Vec;
+use embassy_time::{Duration, Instant, with_timeout};
+use heapless::{String, Vec};
 use reqwless::client::{HttpClient, TlsConfig, TlsVerify};
 use reqwless::request::Method;
 use static_cell::StaticCell;
 
 use crate::config::ProxyConfig;
-use crate::config::TflApiRequestConfig;
+use crate::config::{NamesConfig, SmoothingConfig, TflApiRequestConfig};
+use crate::filters::names::NameNormaliser;
+use crate::filters::smoothing::CountdownSmoother;
+use crate::filters::tracker::VehicleTracker;
+use crate::models::TFL_API_FIELD_LONG_STR_SIZE;
+use crate::models::bounded::BoundedString;
 use crate::models::prediction::{ARRAY_MAX_SIZE_PREDICTION_MODEL, Prediction};
-use crate::models::status::{ARRAY_MAX_SIZE_LINE_STATUS_MODEL, Status};
+use crate::models::status::{ARRAY_MAX_SIZE_LINE_STATUS_MODEL, PlannedStatus, Status};
+use crate::models::system::SystemState;
+use crate::models::update::ARRAY_MAX_SIZE_CLOSURES;
+use crate::models::wire::{WireError, WireUpdate};
+use crate::secrets;
+use crate::system;
+use crate::tasks::ntp::{WALL_CLOCK, london_weekend, unix_to_london_date};
 use crate::{NOTIFY, SCHEDULE, UPDATE};
 
+mod url;
+
+use url::{REQUEST_URL_SIZE, arrivals_url, line_status_url, planned_status_url, update_url};
+
 // Static buffers for TLS client
 static TLS_READ_BUF: StaticCell<[u8; 24576]> = StaticCell::new();
 static TLS_WRITE_BUF: StaticCell<[u8; 16640]> = StaticCell::new();
 static HTTP_RX_BUF: StaticCell<[u8; 16384]> = StaticCell::new();
 static TCP_STATE: StaticCell<TcpClientState<1, 24576, 4096>> = StaticCell::new();
 
+// Age of a proxy update beyond which the proxy is likely serving a cached update
+const STALE_UPDATE_SECS: u64 = 120;
+
+// Wait after a failed planned closures request, rather than retrying a response too large or
+// unparsable every cycle
+const CLOSURES_RETRY: Duration = Duration::from_secs(3600);
+
 #[named]
 #[embassy_executor::task(pool_size = 1)]
 pub async fn request_task(stack: Stack<'static>) {
@@ -48,6 +69,13 @@ pub async fn request_task(stack: Stack<'static>) {
     let tls_write_buffer = TLS_WRITE_BUF.init([0; 16640]);
     let rx_buffer = HTTP_RX_BUF.init([0; 16384]);
     let client_state = TCP_STATE.init(TcpClientState::<1, 24576, 4096>::new());
+    let proxy_config = ProxyConfig::new();
+    let mut tracker = VehicleTracker::new();
+    let mut smoother = CountdownSmoother::new(SmoothingConfig::new());
+    let name_normaliser = NameNormaliser::new(NamesConfig::new());
+    let mut last_processed: Option<Instant> = None;
+    let mut closures_fetched_on: Option<(i32, u32, u32, u32)> = None;
+    let mut closures_retry_at: Option<Instant> = None;
 
     loop {
         // Handle scheduled sleep
@@ -82,73 +110,181 @@ pub async fn request_task(stack: Stack<'static>) {
         let mut http_client = HttpClient::new_with_tls(&tcp_client, &dns_client, tls_config);
 
         // Make the API requests
-
-        // Request station & platform arrival predictions
-        info!("{}: Making Prediction API request", function_name!());
-        let fetched_predictions = match with_timeout(
-            Duration::from_secs(10),
-            request_prediction(&mut http_client, rx_buffer),
-        )
-        .await
-        {
-            Ok(Some(predictions)) => {
-                debug!("{}: predictions = {}", function_name!(), predictions);
-                Some(predictions)
-            }
-            Ok(None) => {
-                error!("Predictions API returned an empty or unparsable payload");
-                None
-            }
-            Err(_) => {
-                error!("Predictions network request timed out!");
-                None
-            }
-        };
-
-        // Request (line) status (all okay, minor delays, ...)
-        info!("{}: Making Status API request", function_name!());
-        let fetched_status = match with_timeout(
-            Duration::from_secs(10),
-            request_status(&mut http_client, rx_buffer),
-        )
-        .await
-        {
-            Ok(Some(status)) => {
-                debug!("{}: status = {}", function_name!(), status);
-                Some(status)
-            }
-            Ok(None) => {
-                error!("Status API returned an empty or unparsable payload");
-                None
-            }
-            Err(_) => {
-                error!("Status network request timed out!");
-                None
+        if proxy_config.binary_updates {
+            // Request the collated update from the proxy, in place of the individual API requests
+            info!("{}: Making proxy Update request", function_name!());
+            match with_timeout(
+                Duration::from_secs(10),
+                request_update(&mut http_client, rx_buffer),
+            )
+            .await
+            {
+                Ok(Some(Ok(mut wire_update))) => {
+                    debug!("{}: wire update = {}", function_name!(), wire_update);
+                    system::request_succeeded();
+                    let now = WALL_CLOCK.lock(|cell| cell.borrow().current_unix());
+                    if let Some(age_secs) = now.map(|now| wire_update.age_secs(now))
+                        && age_secs > STALE_UPDATE_SECS
+                    {
+                        warn!(
+                            "{}: Proxy update generated {}s ago",
+                            function_name!(),
+                            age_secs
+                        );
+                    }
+                    let elapsed_secs = cycle_elapsed_secs(&mut last_processed);
+                    tracker.process(&mut wire_update.arrivals, elapsed_secs);
+                    smoother.process(&mut wire_update.arrivals, elapsed_secs);
+                    name_normaliser.process(&mut wire_update.arrivals);
+                    wire_update.station_name = name_normaliser.normalise(&wire_update.station_name);
+                    for message in wire_update.messages.iter() {
+                        info!("{}: Proxy message: {}", function_name!(), message);
+                    }
+                    if wire_update.arrivals.is_empty() {
+                        system::set(SystemState::NoPredictions);
+                    } else {
+                        system::set(SystemState::Ready);
+                    }
+                    system::update_boot_progress(|progress| progress.tfl = true);
+                    let mut update = UPDATE.lock().await;
+                    wire_update.apply(&mut update);
+                }
+                Ok(Some(Err(wire_error @ WireError::VersionMismatch { expected, found }))) => {
+                    error!(
+                        "Proxy/firmware mismatch: expected schema version {}, found {}",
+                        expected, found
+                    );
+                    let mut update = UPDATE.lock().await;
+                    update.fault = wire_error.fault();
+                }
+                Ok(Some(Err(WireError::Malformed))) => {
+                    error!("Update API returned an undecodable payload");
+                }
+                Ok(None) => {
+                    error!("Update API returned an empty payload");
+                }
+                Err(_) => {
+                    error!("Update network request timed out!");
+                    system::request_failed(None);
+                }
             }
-        };
+        } else {
+            // Request station & platform arrival predictions
+            info!("{}: Making Prediction API request", function_name!());
+            let fetched_predictions = match with_timeout(
+                Duration::from_secs(10),
+                request_prediction(&mut http_client, rx_buffer),
+            )
+            .await
+            {
+                Ok(Some(predictions)) => {
+                    debug!("{}: predictions = {}", function_name!(), predictions);
+                    system::request_succeeded();
+                    Some(predictions)
+                }
+                Ok(None) => {
+                    error!("Predictions API returned an empty or unparsable payload");
+                    None
+                }
+                Err(_) => {
+                    error!("Predictions network request timed out!");
+                    system::request_failed(None);
+                    None
+                }
+            };
+
+            // Suppress duplicate and ghost predictions, smooth their countdowns, then shorten names
+            let fetched_predictions = fetched_predictions.map(|mut predictions| {
+                let elapsed_secs = cycle_elapsed_secs(&mut last_processed);
+                tracker.process(&mut predictions, elapsed_secs);
+                smoother.process(&mut predictions, elapsed_secs);
+                name_normaliser.process(&mut predictions);
+                predictions
+            });
 
-        // Trigger an update if there are predictions, or to confirm status
-        {
-            let mut update = UPDATE.lock().await;
-
-            // Update predictions data if available
-            if let Some(predictions) = fetched_predictions {
-                if !predictions.is_empty() {
-                    update.line_name = predictions[0].line_name.clone();
-                    update.platform_name = predictions[0].platform_name.clone();
-                    update.station_name = predictions[0].station_name.clone();
-                    update.arrivals = predictions;
+            // Request (line) status (all okay, minor delays, ...)
+            info!("{}: Making Status API request", function_name!());
+            let fetched_status = match with_timeout(
+                Duration::from_secs(10),
+                request_status(&mut http_client, rx_buffer),
+            )
+            .await
+            {
+                Ok(Some(status)) => {
+                    debug!("{}: status = {}", function_name!(), status);
+                    Some(status)
+                }
+                Ok(None) => {
+                    error!("Status API returned an empty or unparsable payload");
+                    None
+                }
+                Err(_) => {
+                    error!("Status network request timed out!");
+                    system::request_failed(None);
+                    None
+                }
+            };
+
+            // Trigger an update if there are predictions, or to confirm status
+            {
+                let mut update = UPDATE.lock().await;
+
+                // Update predictions data if available, once none are retained by the tracker
+                if let Some(predictions) = fetched_predictions {
+                    system::update_boot_progress(|progress| progress.tfl = true);
+                    if predictions.is_empty() {
+                        system::set(SystemState::NoPredictions);
+                    } else {
+                        system::set(SystemState::Ready);
+                        update.line_name = predictions[0].line_name.clone();
+                        update.platform_name = predictions[0].platform_name.clone();
+                        update.station_name = predictions[0].station_name.clone();
+                        update.arrivals = predictions;
+                    }
+                }
+
+                // Line Status with fallback logic
+                if let Some(status) = fetched_status {
+                    if let Some(line_status) = status.line_statuses.first() {
+                        // Explicit warning/alert state from the API :(
+                        update.line_status = line_status.status_severity_description.clone();
+                    } else {
+                        // No status data returned = everything is running perfectly fine!
+                        update.line_status = BoundedString::truncating("Good Service");
+                    }
                 }
             }
+        }
 
-            // Line Status with fallback logic
-            if let Some(status) = fetched_status {
-                if let Some(line_status) = status.line_statuses.first() {
-                    // Explicit warning/alert state from the API :(
-                    update.line_status = line_status.status_severity_description.clone();
-                } else {
-                    // No status data returned = everything is running perfectly fine!
-                    update.line_status = String::try_from("Good Service").unwrap_or_default();
+        // Request the planned closures for the weekend, once a day as they rarely change, and with
+        // or without the proxy, as they are not part of its update
+        // N.B. the weekend is not known until the clock is synced
+        let now = WALL_CLOCK.lock(|cell| cell.borrow().current_unix());
+        if let Some(now) = now
+            && closures_fetched_on != Some(unix_to_london_date(now))
+            && closures_retry_at.is_none_or(|retry_at| Instant::now() >= retry_at)
+        {
+            info!("{}: Making planned Status API request", function_name!());
+            match with_timeout(
+                Duration::from_secs(10),
+                request_closures(&mut http_client, rx_buffer, london_weekend(now)),
+            )
+            .await
+            {
+                Ok(Some(closures)) => {
+                    debug!("{}: closures = {}", function_name!(), closures);
+                    closures_fetched_on = Some(unix_to_london_date(now));
+                    closures_retry_at = None;
+                    UPDATE.lock().await.closures = closures;
+                }
+                Ok(None) => {
+                    error!("Planned Status API returned an empty or unparsable payload");
+                    closures_retry_at = Some(Instant::now() + CLOSURES_RETRY);
+                }
+                Err(_) => {
+                    error!("Planned Status network request timed out!");
+                    system::request_failed(None);
+                    closures_retry_at = Some(Instant::now() + CLOSURES_RETRY);
                 }
             }
         }
@@ -169,19 +305,17 @@ async fn request_prediction<const RX_SZ: usize, const TX_SZ: usize>(
     // define the URL for the TFL API request
     let tfl_api_request_config = TflApiRequestConfig::new();
     let proxy_config = ProxyConfig::new();
-    let mut url_buffer: String<256> = String::new();
-    let url = match write!(
-        &mut url_buffer,
-        "{}/StopPoint/{}/Arrivals?api_key={}",
+    let url = match arrivals_url(
         proxy_config.http_proxy,
         tfl_api_request_config.stopcode,
-        tfl_api_request_config.api_primary_key
+        &secrets::current().api_primary_key,
     ) {
-        Ok(_) => url_buffer.as_str(),
+        Ok(url) => url,
         Err(e) => {
             error!(
-                "{}: URL generation failed: Stack buffer size of 256 bytes was too small!: {}",
+                "{}: URL generation failed, longer than {} bytes: {}",
                 function_name!(),
+                REQUEST_URL_SIZE,
                 e
             );
             None?
@@ -192,10 +326,11 @@ async fn request_prediction<const RX_SZ: usize, const TX_SZ: usize>(
     info!("{}: connecting to {}", function_name!(), &url);
 
     // Make HTTP request
-    let mut request = match http_client.request(Method::GET, &url).await {
+    let mut request = match http_client.request(Method::GET, url.as_str()).await {
         Ok(req) => req,
         Err(e) => {
             error!("{}: Failed to make HTTP request: {}", function_name!(), e);
+            report_request_error(&e);
             None?
         }
     };
@@ -205,15 +340,22 @@ async fn request_prediction<const RX_SZ: usize, const TX_SZ: usize>(
         Ok(resp) => resp,
         Err(e) => {
             error!("{}: Failed to send HTTP request: {}", function_name!(), e);
+            report_request_error(&e);
             None?
         }
     };
 
+    // Stop if the API key was rejected, or the request otherwise failed
+    if !check_status(response.status.0) {
+        return None;
+    }
+
     // Read response body
     let body = match response.body().read_to_end().await {
         Ok(body) => body,
-        Err(_) => {
-            error!("{}: Failed to read response body", function_name!());
+        Err(e) => {
+            error!("{}: Failed to read response body: {}", function_name!(), e);
+            system::request_failed(None);
             return None;
         }
     };
@@ -233,31 +375,23 @@ async fn request_prediction<const RX_SZ: usize, const TX_SZ: usize>(
                 predictions.len()
             );
 
+            // Left empty for the tracker, which may retain a train missing from this response
             if predictions.is_empty() {
-                error!(
+                warn!(
                     "{}: API returned a valid JSON array, but it was empty!",
                     function_name!()
                 );
-                return None;
             }
 
-            // Filter only for platform of interest
-            predictions.retain(|p| {
-                p.platform_name
-                    .contains(tfl_api_request_config.platform_name)
-            });
+            retain_platform_arrivals(&mut predictions, tfl_api_request_config.platform_name);
 
             if predictions.is_empty() {
                 warn!(
                     "{}: No predictions retained after filtering for platform on interest",
                     function_name!()
                 );
-                return None;
             }
 
-            // Sort array by which is arriving first
-            predictions.sort_unstable_by_key(|p| p.time_to_station);
-
             Some(predictions)
         }
         Err(e) => {
@@ -271,6 +405,139 @@ async fn request_prediction<const RX_SZ: usize, const TX_SZ: usize>(
     }
 }
 
+#[named]
+async fn request_update<const RX_SZ: usize, const TX_SZ: usize>(
+    http_client: &mut HttpClient<'_, TcpClient<'_, 1, RX_SZ, TX_SZ>, DnsSocket<'_>>,
+    rx_buffer: &mut [u8],
+) -> Option<Result<WireUpdate, WireError>> {
+    // define the URL for the proxy request
+    let tfl_api_request_config = TflApiRequestConfig::new();
+    let proxy_config = ProxyConfig::new();
+    let url = match update_url(
+        proxy_config.http_proxy,
+        tfl_api_request_config.line_id,
+        tfl_api_request_config.stopcode,
+        &secrets::current().api_primary_key,
+    ) {
+        Ok(url) => url,
+        Err(e) => {
+            error!(
+                "{}: URL generation failed, longer than {} bytes: {}",
+                function_name!(),
+                REQUEST_URL_SIZE,
+                e
+            );
+            return None;
+        }
+    };
+
+    // Make the HTTP request to the proxy
+    info!("{}: connecting to {}", function_name!(), &url);
+
+    let mut request = match http_client.request(Method::GET, url.as_str()).await {
+        Ok(req) => req,
+        Err(e) => {
+            error!("{}: Failed to make HTTP request: {}", function_name!(), e);
+            report_request_error(&e);
+            return None;
+        }
+    };
+
+    // Send HTTP request
+    let response = match request.send(rx_buffer).await {
+        Ok(resp) => resp,
+        Err(e) => {
+            error!("{}: Failed to send HTTP request: {}", function_name!(), e);
+            report_request_error(&e);
+            return None;
+        }
+    };
+
+    // Stop if the API key was rejected, or the request otherwise failed
+    if !check_status(response.status.0) {
+        return None;
+    }
+
+    // Read response body
+    let body = match response.body().read_to_end().await {
+        Ok(body) => body,
+        Err(e) => {
+            error!("{}: Failed to read response body: {}", function_name!(), e);
+            system::request_failed(None);
+            return None;
+        }
+    };
+
+    if body.is_empty() {
+        return None;
+    }
+
+    // Decode the binary payload, checking the schema version first
+    info!(
+        "{}: About to decode payload. Total bytes in body variable: {}",
+        function_name!(),
+        body.len()
+    );
+    let result = WireUpdate::decode(body).map(|mut wire_update| {
+        retain_platform_arrivals(
+            &mut wire_update.arrivals,
+            tfl_api_request_config.platform_name,
+        );
+        wire_update
+    });
+
+    Some(result)
+}
+
+/// Set the system state for a failed request, by its cause
+fn report_request_error(e: &reqwless::Error) {
+    match e {
+        reqwless::Error::Dns => system::set(SystemState::DnsFailed),
+        reqwless::Error::Tls(_) => system::set(SystemState::TlsFailed),
+        // e.g. refused, reset, or the network is down
+        _ => system::request_failed(None),
+    }
+}
+
+/// Whether the request succeeded, by the HTTP status, setting the system state if not
+fn check_status(status: u16) -> bool {
+    match status {
+        200..=299 => true,
+        401 | 403 => {
+            error!("API key rejected with HTTP status {}", status);
+            system::set(SystemState::AuthFailed { status });
+            false
+        }
+        _ => {
+            error!("API request failed with HTTP status {}", status);
+            system::request_failed(Some(status));
+            false
+        }
+    }
+}
+
+/// Seconds since predictions were last processed, restarting the measurement
+fn cycle_elapsed_secs(last_processed: &mut Option<Instant>) -> u32 {
+    let now = Instant::now();
+    let elapsed_secs = last_processed
+        .map(|t| (now - t).as_secs() as u32)
+        .unwrap_or(0);
+    *last_processed = Some(now);
+    elapsed_secs
+}
+
+/// Retain only the predictions for the platform of interest, sorted by which is arriving first
+fn retain_platform_arrivals(
+    predictions: &mut Vec<Prediction, ARRAY_MAX_SIZE_PREDICTION_MODEL>,
+    platform_name: &str,
+) {
+    // Filter only for platform of interest
+    predictions.retain(|p| p.platform_name.contains(platform_name));
+
+    // Sort array by which is arriving first
+    predictions.sort_unstable_by_key(|p| p.time_to_station);
+}
+
 #[named]
 pub async fn request_status<const RX_SZ: usize, const TX_SZ: usize>(
     http_client: &mut HttpClient<'_, TcpClient<'_, 1, RX_SZ, TX_SZ>, DnsSocket<'_>>,
@@ -279,22 +546,17 @@ pub async fn request_status<const RX_SZ: usize, const TX_SZ: usize>(
     // 1. Dynamic URL Generation mirroring request_prediction
     let tfl_api_request_config = TflApiRequestConfig::new();
     let proxy_config = ProxyConfig::new();
-    let mut url_buffer: String<256> = String::new();
-
-    // Hardcoded line ID parameters are replaced by runtime configs
-    // Assuming tfl_api_request_config contains or can provide your target line ID
-    let url = match write!(
-        &mut url_buffer,
-        "{}/Line/{}/Status?api_key={}",
+    let url = match line_status_url(
         proxy_config.http_proxy,
         tfl_api_request_config.line_id,
-        tfl_api_request_config.api_primary_key
+        &secrets::current().api_primary_key,
     ) {
-        Ok(_) => url_buffer.as_str(),
+        Ok(url) => url,
         Err(e) => {
             error!(
-                "{}: URL generation failed: Stack buffer size of 256 bytes was too small!: {}",
+                "{}: URL generation failed, longer than {} bytes: {}",
                 function_name!(),
+                REQUEST_URL_SIZE,
                 e
             );
             return None;
@@ -304,10 +566,11 @@ pub async fn request_status<const RX_SZ: usize, const TX_SZ: usize>(
     // 2. Make the HTTP request to the TFL API
     info!("{}: connecting to {}", function_name!(), &url);
 
-    let mut request = match http_client.request(Method::GET, &url).await {
+    let mut request = match http_client.request(Method::GET, url.as_str()).await {
         Ok(req) => req,
         Err(e) => {
             error!("{}: Failed to make HTTP request: {}", function_name!(), e);
+            report_request_error(&e);
             return None;
         }
     };
@@ -317,15 +580,22 @@ pub async fn request_status<const RX_SZ: usize, const TX_SZ: usize>(
         Ok(resp) => resp,
         Err(e) => {
             error!("{}: Failed to send HTTP request: {}", function_name!(), e);
+            report_request_error(&e);
             return None;
         }
     };
 
+    // Stop if the API key was rejected, or the request otherwise failed
+    if !check_status(response.status.0) {
+        return None;
+    }
+
     // 4. Read response body
     let body = match response.body().read_to_end().await {
         Ok(body) => body,
-        Err(_) => {
-            error!("{}: Failed to read response body", function_name!());
+        Err(e) => {
+            error!("{}: Failed to read response body: {}", function_name!(), e);
+            system::request_failed(None);
             return None;
         }
     };
@@ -375,3 +645,134 @@ pub async fn request_status<const RX_SZ: usize, const TX_SZ: usize>(
         }
     }
 }
+
+#[named]
+async fn request_closures<const RX_SZ: usize, const TX_SZ: usize>(
+    http_client: &mut HttpClient<'_, TcpClient<'_, 1, RX_SZ, TX_SZ>, DnsSocket<'_>>,
+    rx_buffer: &mut [u8],
+    weekend: ((i32, u32, u32), (i32, u32, u32)),
+) -> Option<Vec<BoundedString<TFL_API_FIELD_LONG_STR_SIZE>, ARRAY_MAX_SIZE_CLOSURES>> {
+    // Dates of the weekend, e.g. 2025-10-25
+    let mut dates: [String<16>; 2] = Default::default();
+    for (date, (year, month, day)) in dates.iter_mut().zip([weekend.0, weekend.1]) {
+        let _ = write!(date, "{:04}-{:02}-{:02}", year, month, day);
+    }
+
+    // define the URL for the TFL API request
+    let tfl_api_request_config = TflApiRequestConfig::new();
+    let proxy_config = ProxyConfig::new();
+    let url = match planned_status_url(
+        proxy_config.http_proxy,
+        tfl_api_request_config.line_id,
+        (&dates[0], &dates[1]),
+        &secrets::current().api_primary_key,
+    ) {
+        Ok(url) => url,
+        Err(e) => {
+            error!(
+                "{}: URL generation failed, longer than {} bytes: {}",
+                function_name!(),
+                REQUEST_URL_SIZE,
+                e
+            );
+            return None;
+        }
+    };
+
+    // Make the HTTP request to the TFL API
+    info!("{}: connecting to {}", function_name!(), &url);
+
+    let mut request = match http_client.request(Method::GET, url.as_str()).await {
+        Ok(req) => req,
+        Err(e) => {
+            error!("{}: Failed to make HTTP request: {}", function_name!(), e);
+            report_request_error(&e);
+            return None;
+        }
+    };
+
+    // Send HTTP request
+    let response = match request.send(rx_buffer).await {
+        Ok(resp) => resp,
+        Err(e) => {
+            error!("{}: Failed to send HTTP request: {}", function_name!(), e);
+            report_request_error(&e);
+            return None;
+        }
+    };
+
+    // Stop if the API key was rejected, or the request otherwise failed
+    if !check_status(response.status.0) {
+        return None;
+    }
+
+    // Read response body
+    let body = match response.body().read_to_end().await {
+        Ok(body) => body,
+        Err(e) => {
+            error!("{}: Failed to read response body: {}", function_name!(), e);
+            system::request_failed(None);
+            return None;
+        }
+    };
+
+    info!(
+        "{}: About to deserialize payload. Total bytes in body variable: {}",
+        function_name!(),
+        body.len()
+    );
+
+    // Process JSON objects in body
+    match serde_json_core::de::from_slice::<Vec<PlannedStatus, ARRAY_MAX_SIZE_LINE_STATUS_MODEL>>(
+        &body,
+    ) {
+        Ok((statuses, _used)) => {
+            let Some(status) = statuses.first() else {
+                error!(
+                    "{}: API returned a valid JSON array, but it was empty!",
+                    function_name!()
+                );
+                return None;
+            };
+
+            Some(planned_closures(status))
+        }
+        Err(e) => {
+            error!(
+                "{}: Deserialisation failed with error: {:?}",
+                function_name!(),
+                defmt::Debug2Format(&e)
+            );
+            None
+        }
+    }
+}
+
+/// The reasons for any planned closures, without the line name they begin with
+fn planned_closures(
+    status: &PlannedStatus,
+) -> Vec<BoundedString<TFL_API_FIELD_LONG_STR_SIZE>, ARRAY_MAX_SIZE_CLOSURES> {
+    let mut closures = Vec::new();
+
+    // e.g. "Part Closure", "Planned Closure"
+    let line_statuses = status
+        .line_statuses
+        .iter()
+        .filter(|line_status| line_status.status_severity_description.contains("Closure"));
+
+    for line_status in line_statuses {
+        // e.g. "DISTRICT LINE: Saturday 25 and Sunday 26 October, no service between..."
+        let reason = line_status
+            .reason
+            .split_once(": ")
+            .map_or(line_status.reason.as_str(), |(_, reason)| reason);
+        let closure = BoundedString::truncating(reason.trim());
+
+        // The same closure is listed for each of its periods
+        if !closures.contains(&closure) {
+            let _ = closures.push(closure);
+        }
+    }
+
+    closures
+}
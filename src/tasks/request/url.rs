@@ -0,0 +1,165 @@
+//! URL builder
+//!
+//! A small no_std builder for the TFL API (or proxy) request URLs, in place of
+//! hand formatting them. Path segments and query parameters are
+//! percent-encoded, empty query parameters (e.g. an unset API key) are
+//! omitted, and exceeding the capacity of the URL is a typed error, rather
+//! than a silently truncated URL.
+//!
+//! e.g. `{proxy}/Line/{line_id}/Status?api_key={key}`
+//!
+//! ```ignore
+//! let url = UrlBuilder::<256>::new("https://api.tfl.gov.uk")
+//!     .segment("Line")
+//!     .segment("district")
+//!     .segment("Status")
+//!     .query("api_key", "")
+//!     .build()?; // https://api.tfl.gov.uk/Line/district/Status
+//! ```
+//!
+use defmt::Format;
+use heapless::String;
+
+// Capacity of the request URLs
+pub const REQUEST_URL_SIZE: usize = 256;
+
+pub type RequestUrl = String<REQUEST_URL_SIZE>;
+
+/// Represents any error that may happen when building a URL.
+#[derive(Copy, Clone, Debug, Format, Eq, PartialEq)]
+pub enum UrlError {
+    /// The URL is longer than the capacity of the buffer
+    CapacityExceeded,
+}
+
+pub struct UrlBuilder<const N: usize> {
+    url: String<N>,
+    has_query: bool,
+    error: Option<UrlError>,
+}
+
+impl<const N: usize> UrlBuilder<N> {
+    /// Start from a base URL (scheme, authority and optional path), which is used verbatim
+    pub fn new(base: &str) -> Self {
+        let mut builder = Self {
+            url: String::new(),
+            has_query: false,
+            error: None,
+        };
+        builder.push_str(base.trim_end_matches('/'));
+        builder
+    }
+
+    /// Append a percent-encoded path segment
+    pub fn segment(mut self, segment: &str) -> Self {
+        self.push_str("/");
+        self.push_encoded(segment);
+        self
+    }
+
+    /// Append a percent-encoded query parameter, omitted if the value is empty
+    pub fn query(mut self, key: &str, value: &str) -> Self {
+        if value.is_empty() {
+            return self;
+        }
+
+        self.push_str(if self.has_query { "&" } else { "?" });
+        self.has_query = true;
+        self.push_encoded(key);
+        self.push_str("=");
+        self.push_encoded(value);
+        self
+    }
+
+    /// Finish the URL, or the first error encountered whilst building it
+    pub fn build(self) -> Result<String<N>, UrlError> {
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(self.url),
+        }
+    }
+
+    fn push_str(&mut self, s: &str) {
+        if self.error.is_none() && self.url.push_str(s).is_err() {
+            self.error = Some(UrlError::CapacityExceeded);
+        }
+    }
+
+    fn push_char(&mut self, c: char) {
+        if self.error.is_none() && self.url.push(c).is_err() {
+            self.error = Some(UrlError::CapacityExceeded);
+        }
+    }
+
+    /// Push, percent-encoding all but the unreserved characters of RFC 3986
+    fn push_encoded(&mut self, s: &str) {
+        const HEX: &[u8; 16] = b"0123456789ABCDEF";
+
+        for byte in s.bytes() {
+            match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                    self.push_char(byte as char);
+                }
+                _ => {
+                    self.push_char('%');
+                    self.push_char(HEX[(byte >> 4) as usize] as char);
+                    self.push_char(HEX[(byte & 0x0F) as usize] as char);
+                }
+            }
+        }
+    }
+}
+
+/// Arrivals at the stop, `{base}/StopPoint/{stopcode}/Arrivals`
+pub fn arrivals_url(base: &str, stopcode: &str, api_key: &str) -> Result<RequestUrl, UrlError> {
+    UrlBuilder::new(base)
+        .segment("StopPoint")
+        .segment(stopcode)
+        .segment("Arrivals")
+        .query("api_key", api_key)
+        .build()
+}
+
+/// Update collated by the proxy, `{base}/Update/{line_id}/{stopcode}`
+pub fn update_url(
+    base: &str,
+    line_id: &str,
+    stopcode: &str,
+    api_key: &str,
+) -> Result<RequestUrl, UrlError> {
+    UrlBuilder::new(base)
+        .segment("Update")
+        .segment(line_id)
+        .segment(stopcode)
+        .query("api_key", api_key)
+        .build()
+}
+
+/// Current status of the line, `{base}/Line/{line_id}/Status`
+pub fn line_status_url(base: &str, line_id: &str, api_key: &str) -> Result<RequestUrl, UrlError> {
+    UrlBuilder::new(base)
+        .segment("Line")
+        .segment(line_id)
+        .segment("Status")
+        .query("api_key", api_key)
+        .build()
+}
+
+/// Status of the line between the dates, for planned closures,
+/// `{base}/Line/{line_id}/Status/{from}/to/{to}`
+pub fn planned_status_url(
+    base: &str,
+    line_id: &str,
+    (from, to): (&str, &str),
+    api_key: &str,
+) -> Result<RequestUrl, UrlError> {
+    UrlBuilder::new(base)
+        .segment("Line")
+        .segment(line_id)
+        .segment("Status")
+        .segment(from)
+        .segment("to")
+        .segment(to)
+        .query("api_key", api_key)
+        .build()
+}